@@ -3,4 +3,9 @@ use bevy::prelude::Component;
 /// Bomb Component
 #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
-pub struct Bomb;
\ No newline at end of file
+pub struct Bomb {
+    // Number of bombs stacked on the tile
+    pub count: u8,
+    // Do the bombs subtract from the neighbor numbers?
+    pub negative: bool,
+}
\ No newline at end of file
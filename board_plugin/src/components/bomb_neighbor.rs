@@ -1,10 +1,9 @@
 use bevy::prelude::Component;
 
 ///Bomb neighbor component
-
 #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
 pub struct BombNeighbor {
     // Number of neighbor bombs
-    pub count: u8,
+    pub count: i16,
 }
\ No newline at end of file
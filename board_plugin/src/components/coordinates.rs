@@ -1,12 +1,16 @@
 use std::fmt::{self, Display, Formatter};
 use std::ops::{Add, Sub};
+#[cfg(feature = "plugin")]
 use bevy::prelude::Component;
 
 #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
-#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+#[cfg_attr(feature = "plugin", derive(Component))]
+/// Tile position. Bounded boards only use positive coordinates, endless boards span the whole
+/// signed range
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct Coordinates {
-    pub x: u16,
-    pub y: u16,
+    pub x: i32,
+    pub y: i32,
 }
 
 // coordinate sum functionality
@@ -28,9 +32,10 @@ impl Add<(i8, i8)> for Coordinates {
     type Output = Self;
 
     fn add(self, (x,y): (i8, i8)) -> Self::Output {
-        let x = ((self.x as i16) + x as i16) as u16;
-        let y = ((self.y as i16) + y as i16) as u16;
-        Self { x, y }
+        Self {
+            x: self.x + x as i32,
+            y: self.y + y as i32,
+        }
     }
 }
 
@@ -38,9 +43,8 @@ impl Sub for Coordinates {
     type Output = Self;
     fn sub(self, rhs: Self) -> Self::Output {
        Self {
-           // saturating sub avoids *panic* if the subtraction result is negative
-           x: self.x.saturating_sub(rhs.x),
-           y: self.y.saturating_sub(rhs.y),
+           x: self.x - rhs.x,
+           y: self.y - rhs.y,
        }
     }
 }
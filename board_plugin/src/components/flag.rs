@@ -0,0 +1,11 @@
+use bevy::prelude::Component;
+
+/// Flag component, indicates a tile cover marked by the player as a bomb
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct Flag;
+
+/// Marks the flag sprite and flag count text children of a tile cover
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct FlagSprite;
@@ -0,0 +1,11 @@
+use bevy::prelude::Component;
+
+/// Heatmap overlay component, indicates a sprite tinting a tile cover by its bomb probability
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct HeatmapOverlay;
+
+/// Heatmap label component, indicates the text showing the bomb probability of the hovered tile
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct HeatmapLabel;
@@ -0,0 +1,6 @@
+use bevy::prelude::Component;
+
+/// Hint component, indicates a tile cover highlighted by a hint
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct Hint;
@@ -1,9 +1,27 @@
 mod coordinates;
+#[cfg(feature = "plugin")]
 mod bomb;
+#[cfg(feature = "plugin")]
 mod bomb_neighbor;
+#[cfg(feature = "plugin")]
 mod uncover;
+#[cfg(feature = "plugin")]
+mod flag;
+#[cfg(feature = "plugin")]
+mod hint;
+#[cfg(feature = "plugin")]
+mod heatmap;
 
 pub use coordinates::Coordinates;
+#[cfg(feature = "plugin")]
 pub use bomb::Bomb;
+#[cfg(feature = "plugin")]
 pub use bomb_neighbor::BombNeighbor;
-pub use uncover::Uncover;
\ No newline at end of file
+#[cfg(feature = "plugin")]
+pub use uncover::Uncover;
+#[cfg(feature = "plugin")]
+pub use flag::{Flag, FlagSprite};
+#[cfg(feature = "plugin")]
+pub use hint::Hint;
+#[cfg(feature = "plugin")]
+pub use heatmap::{HeatmapLabel, HeatmapOverlay};
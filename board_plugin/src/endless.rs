@@ -0,0 +1,324 @@
+use crate::components::Coordinates;
+use crate::game::{GameOutcome, RevealedTile};
+use crate::resources::tile::Tile;
+use crate::resources::tile_map::TileMap;
+use crate::resources::{BoardOptionsError, Topology};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Side of the square chunks an endless board is generated by
+pub const CHUNK_SIZE: i32 = 16;
+
+/// Number of tiles of a chunk
+const CHUNK_TILES: u16 = (CHUNK_SIZE * CHUNK_SIZE) as u16;
+
+/// Position of a chunk, in chunks from the chunk holding the origin tile
+#[cfg_attr(feature = "plugin", derive(bevy::prelude::Component))]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ChunkCoordinates {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl ChunkCoordinates {
+    /// Chunk holding the tile at 'coords'
+    pub fn of(coords: Coordinates) -> Self {
+        Self {
+            x: coords.x.div_euclid(CHUNK_SIZE),
+            y: coords.y.div_euclid(CHUNK_SIZE),
+        }
+    }
+
+    /// Bottom left tile of the chunk
+    pub fn origin(&self) -> Coordinates {
+        Coordinates {
+            x: self.x * CHUNK_SIZE,
+            y: self.y * CHUNK_SIZE,
+        }
+    }
+
+    /// Every tile of the chunk, line by line
+    pub fn tiles(&self) -> impl Iterator<Item = Coordinates> {
+        let origin = self.origin();
+        (0..CHUNK_SIZE).flat_map(move |y| {
+            (0..CHUNK_SIZE).map(move |x| Coordinates {
+                x: origin.x + x,
+                y: origin.y + y,
+            })
+        })
+    }
+
+    /// The chunk and the 8 chunks around it
+    fn square(&self) -> impl Iterator<Item = ChunkCoordinates> {
+        let center = *self;
+        (-1..=1).flat_map(move |y| {
+            (-1..=1).map(move |x| ChunkCoordinates {
+                x: center.x + x,
+                y: center.y + y,
+            })
+        })
+    }
+}
+
+/// Endless board options. Must be used as a resource
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndlessOptions {
+    // World seed, a random one is picked if not set
+    pub seed: Option<u64>,
+    // Bombs of each chunk, from an eighth of its tiles
+    pub bombs_per_chunk: u16,
+    // Layout of the tiles, used by the plugin
+    pub tile_size: f32,
+    pub tile_padding: f32,
+}
+
+impl Default for EndlessOptions {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            bombs_per_chunk: 40,
+            tile_size: 30.,
+            tile_padding: 1.,
+        }
+    }
+}
+
+impl EndlessOptions {
+    /// Checks that an endless board can be generated from these options
+    pub fn validate(&self) -> Result<(), BoardOptionsError> {
+        // Sparser boards could have openings spreading forever. The tiles around the origin are
+        // kept free, and are at most a whole 3x3 square in a chunk
+        let (min, max) = (CHUNK_TILES / 8, CHUNK_TILES - 9);
+        if !(min..=max).contains(&self.bombs_per_chunk) {
+            return Err(BoardOptionsError::InvalidBombDensity {
+                bombs_per_chunk: self.bombs_per_chunk,
+                min,
+                max,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Generated part of an endless board
+#[derive(Debug, Clone)]
+struct Chunk {
+    // bombs of the chunk, its numbers ignore the neighbor chunks
+    bombs: TileMap,
+    revealed: Vec<bool>,
+    flagged: Vec<bool>,
+}
+
+/// Minesweeper game on a board without edges. The bombs of a chunk are generated from the world
+/// seed when a reveal or the view first needs them, so the same seed always gives the same world.
+///
+/// The game starts opened on the origin tile and ends at the first explosion, the score being the
+/// number of tiles cleared until then
+#[derive(Debug, Clone)]
+pub struct EndlessGame {
+    seed: u64,
+    bombs_per_chunk: u16,
+    chunks: HashMap<ChunkCoordinates, Chunk>,
+    // number of revealed tiles
+    cleared: u64,
+    outcome: GameOutcome,
+}
+
+impl EndlessGame {
+    /// Generates a new endless game, opened on the origin tile
+    pub fn new(options: &EndlessOptions) -> Result<Self, BoardOptionsError> {
+        options.validate()?;
+        let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut game = Self {
+            seed,
+            bombs_per_chunk: options.bombs_per_chunk,
+            chunks: HashMap::new(),
+            cleared: 0,
+            outcome: GameOutcome::Running,
+        };
+        game.reveal(Coordinates::default());
+        Ok(game)
+    }
+
+    /// Getter for 'seed'
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Number of tiles cleared
+    pub fn score(&self) -> u64 {
+        self.cleared
+    }
+
+    /// Getter for 'outcome', an endless game is never won
+    pub fn outcome(&self) -> GameOutcome {
+        self.outcome
+    }
+
+    /// Did a bomb explode?
+    pub fn is_over(&self) -> bool {
+        self.outcome != GameOutcome::Running
+    }
+
+    /// Generates the bombs of 'chunk' and of its neighbors, so that every tile of the chunk can
+    /// be read
+    pub fn load_chunk(&mut self, chunk: ChunkCoordinates) {
+        for chunk in chunk.square() {
+            if !self.chunks.contains_key(&chunk) {
+                let generated = self.generate_chunk(chunk);
+                self.chunks.insert(chunk, generated);
+            }
+        }
+    }
+
+    /// Tile at 'coords', `None` if its chunk isn't loaded
+    pub fn tile_at(&self, coords: Coordinates) -> Option<Tile> {
+        if self.is_bomb_at(coords)? {
+            return Some(Tile::Bomb(1));
+        }
+        let mut count = 0;
+        for delta in Topology::Square.neighbor_deltas(coords) {
+            if self.is_bomb_at(coords + *delta)? {
+                count += 1;
+            }
+        }
+        Some(match count {
+            0 => Tile::Empty,
+            v => Tile::BombNeighbor(v),
+        })
+    }
+
+    /// Is the tile at 'coords' revealed? Tiles of chunks never generated are not
+    pub fn is_revealed(&self, coords: Coordinates) -> bool {
+        self.chunk_cell(coords)
+            .map_or(false, |(chunk, i)| chunk.revealed[i])
+    }
+
+    /// Is the tile at 'coords' flagged?
+    pub fn is_flagged(&self, coords: Coordinates) -> bool {
+        self.chunk_cell(coords)
+            .map_or(false, |(chunk, i)| chunk.flagged[i])
+    }
+
+    /// Reveals a covered and unflagged tile, opening the empty area it belongs to and its
+    /// numbered border. Returns the revealed tiles in breadth first order, starting with 'coords'
+    pub fn reveal(&mut self, coords: Coordinates) -> Vec<RevealedTile> {
+        self.load_chunk(ChunkCoordinates::of(coords));
+        if !self.can_reveal(coords) {
+            return vec![];
+        }
+        let mut revealed = vec![];
+        // Tiles are revealed when queued so that each one is queued once
+        self.uncover(coords);
+        let mut queue = VecDeque::from([(coords, 0)]);
+        while let Some((coords, depth)) = queue.pop_front() {
+            revealed.push(RevealedTile {
+                coordinates: coords,
+                depth,
+            });
+            self.load_chunk(ChunkCoordinates::of(coords));
+            match self.tile_at(coords) {
+                Some(Tile::Bomb(_)) => {
+                    // the exploded tile isn't cleared
+                    self.cleared -= 1;
+                    self.outcome = GameOutcome::Lost { exploded: coords };
+                    return revealed;
+                }
+                // Empty tiles propagate the reveal to their neighbors, openings are finite at the
+                // allowed bomb densities
+                Some(Tile::Empty) => {
+                    for delta in Topology::Square.neighbor_deltas(coords) {
+                        let neighbor = coords + *delta;
+                        if self.can_reveal(neighbor) {
+                            self.uncover(neighbor);
+                            queue.push_back((neighbor, depth + 1));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        revealed
+    }
+
+    /// Toggles the flag of a covered tile, returning whether it is now flagged
+    pub fn toggle_flag(&mut self, coords: Coordinates) -> Option<bool> {
+        if self.is_over() || self.is_revealed(coords) {
+            return None;
+        }
+        self.load_chunk(ChunkCoordinates::of(coords));
+        let (chunk, i) = self.chunk_cell_mut(coords)?;
+        chunk.flagged[i] = !chunk.flagged[i];
+        Some(chunk.flagged[i])
+    }
+
+    /// Can the tile at 'coords' be revealed by the player?
+    fn can_reveal(&self, coords: Coordinates) -> bool {
+        !self.is_over() && !self.is_revealed(coords) && !self.is_flagged(coords)
+    }
+
+    /// Marks a tile of a generated chunk as revealed
+    fn uncover(&mut self, coords: Coordinates) {
+        if let Some((chunk, i)) = self.chunk_cell_mut(coords) {
+            chunk.revealed[i] = true;
+            self.cleared += 1;
+        }
+    }
+
+    /// Is there a bomb at 'coords'? `None` if its chunk isn't generated
+    fn is_bomb_at(&self, coords: Coordinates) -> Option<bool> {
+        let chunk = self.chunks.get(&ChunkCoordinates::of(coords))?;
+        Some(chunk.bombs.is_bomb_at(local_coordinates(coords)))
+    }
+
+    fn chunk_cell(&self, coords: Coordinates) -> Option<(&Chunk, usize)> {
+        let chunk = self.chunks.get(&ChunkCoordinates::of(coords))?;
+        Some((chunk, local_index(coords)))
+    }
+
+    fn chunk_cell_mut(&mut self, coords: Coordinates) -> Option<(&mut Chunk, usize)> {
+        let chunk = self.chunks.get_mut(&ChunkCoordinates::of(coords))?;
+        Some((chunk, local_index(coords)))
+    }
+
+    /// Places the bombs of a chunk, only from the world seed and the chunk position. The tiles
+    /// around the origin are kept free so that the game starts on an opening
+    fn generate_chunk(&self, chunk: ChunkCoordinates) -> Chunk {
+        let seed = self.seed ^ ((chunk.x as u32 as u64) << 32 | chunk.y as u32 as u64);
+        let origin = Coordinates::default();
+        let excluded: Vec<Coordinates> = std::iter::once(origin)
+            .chain(
+                Topology::Square
+                    .neighbor_deltas(origin)
+                    .iter()
+                    .map(|delta| origin + *delta),
+            )
+            .filter(|c| ChunkCoordinates::of(*c) == chunk)
+            .map(local_coordinates)
+            .collect();
+        let mut bombs = TileMap::empty(CHUNK_SIZE as u16, CHUNK_SIZE as u16);
+        bombs
+            .set_bombs_excluding(self.bombs_per_chunk, &excluded, seed)
+            .expect("the bomb count is validated when the game is created");
+        Chunk {
+            bombs,
+            revealed: vec![false; CHUNK_TILES as usize],
+            flagged: vec![false; CHUNK_TILES as usize],
+        }
+    }
+}
+
+/// Coordinates of a tile in its chunk
+fn local_coordinates(coords: Coordinates) -> Coordinates {
+    Coordinates {
+        x: coords.x.rem_euclid(CHUNK_SIZE),
+        y: coords.y.rem_euclid(CHUNK_SIZE),
+    }
+}
+
+/// Index of a tile in the grids of its chunk
+fn local_index(coords: Coordinates) -> usize {
+    let local = local_coordinates(coords);
+    (local.y * CHUNK_SIZE + local.x) as usize
+}
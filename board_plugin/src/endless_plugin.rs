@@ -0,0 +1,113 @@
+use bevy::ecs::schedule::StateData;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use crate::endless::{EndlessGame, EndlessOptions};
+use crate::resources::endless_board::EndlessBoard;
+use crate::systems;
+use crate::systems::endless::move_cameras;
+
+/// Endless mode, a board without edges streamed by chunks around the camera. The camera is panned
+/// with the arrow or WASD keys
+pub struct EndlessBoardPlugin<T> {
+    pub running_state: T,
+    // State pushed when a bomb explodes, if any
+    pub lost_state: Option<T>,
+}
+
+/// State pushed by the plugin when the endless game ends
+struct EndlessLostState<T>(Option<T>);
+
+impl<T: StateData> Plugin for EndlessBoardPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(self.running_state.clone()).with_system(Self::create_board),
+        )
+        .add_system_set(
+            SystemSet::on_update(self.running_state.clone())
+                .with_system(systems::endless::endless_input_handling)
+                .with_system(systems::endless::camera_movement)
+                .with_system(Self::lost_state_handler),
+        )
+        .add_system_set(
+            SystemSet::on_in_stack_update(self.running_state.clone())
+                .with_system(systems::endless::chunk_streaming),
+        )
+        .add_system_set(
+            SystemSet::on_exit(self.running_state.clone()).with_system(Self::cleanup_board),
+        )
+        .insert_resource(EndlessLostState(self.lost_state.clone()));
+    }
+}
+
+impl<T: StateData> EndlessBoardPlugin<T> {
+    /// System to start an endless game, the chunks are spawned by the streaming system
+    pub fn create_board(
+        mut commands: Commands,
+        options: Option<Res<EndlessOptions>>,
+        mut cameras: Query<&mut Transform, With<OrthographicProjection>>,
+    ) {
+        // if no option is set, use the default one
+        let options = match options {
+            None => EndlessOptions::default(),
+            Some(o) => o.clone(),
+        };
+        let game = match EndlessGame::new(&options) {
+            Ok(g) => g,
+            Err(e) => {
+                log::error!("Failed to generate the endless board: {}", e);
+                return;
+            }
+        };
+        log::info!("endless board seed: {}", game.seed());
+        // The game starts on the origin tile
+        move_cameras(&mut cameras, Vec2::splat(options.tile_size / 2.));
+        let entity = commands
+            .spawn()
+            .insert(Name::new("Endless Board"))
+            .insert(Transform::default())
+            .insert(GlobalTransform::default())
+            .id();
+        commands.insert_resource(EndlessBoard {
+            game,
+            tile_size: options.tile_size,
+            tile_padding: options.tile_padding,
+            chunks: HashMap::default(),
+            entity,
+        });
+    }
+
+    fn cleanup_board(
+        board: Option<Res<EndlessBoard>>,
+        mut commands: Commands,
+        mut cameras: Query<&mut Transform, With<OrthographicProjection>>,
+    ) {
+        // The board is missing if its generation failed
+        if let Some(board) = board {
+            commands.entity(board.entity).despawn_recursive();
+            commands.remove_resource::<EndlessBoard>();
+        }
+        // Other boards are centered on the world origin
+        move_cameras(&mut cameras, Vec2::ZERO);
+    }
+
+    /// Pushes the configured lost state when a bomb explodes
+    fn lost_state_handler(
+        board: Option<Res<EndlessBoard>>,
+        lost_state: Res<EndlessLostState<T>>,
+        mut state: ResMut<State<T>>,
+    ) {
+        let board = match board {
+            Some(b) => b,
+            None => return,
+        };
+        if !board.is_changed() || !board.game.is_over() {
+            return;
+        }
+        if let Some(lost_state) = &lost_state.0 {
+            if let Err(e) = state.push(lost_state.clone()) {
+                log::error!("Failed to push game outcome state: {:?}", e);
+            }
+        }
+    }
+}
@@ -1,6 +1,62 @@
 use crate::components::Coordinates;
+use crate::game::RevealedTile;
+use crate::resources::{BoardOptions, BoardOptionsError};
 
 // An event is like a resource but available for 1 frame
 
 #[derive(Debug, Copy, Clone)]
 pub struct TileTriggerEvent(pub Coordinates);
+
+#[derive(Debug, Copy, Clone)]
+pub struct TileMarkEvent(pub Coordinates);
+
+#[derive(Debug, Copy, Clone)]
+pub struct TileChordEvent(pub Coordinates);
+
+/// Tiles revealed by a single trigger or chord, in the order of the reveal wave
+#[derive(Debug, Clone)]
+pub struct TilesRevealedEvent(pub Vec<RevealedTile>);
+
+#[derive(Debug, Copy, Clone)]
+pub struct BombExplosionEvent(pub Coordinates);
+
+#[derive(Debug, Copy, Clone)]
+pub struct BoardCompletedEvent;
+
+#[derive(Debug, Clone)]
+pub struct BoardErrorEvent(pub BoardOptionsError);
+
+/// Writes the game in progress to the save file
+#[derive(Debug, Copy, Clone)]
+pub struct SaveGameEvent;
+
+/// Replaces the board with the game of the save file
+#[derive(Debug, Copy, Clone)]
+pub struct LoadGameEvent;
+
+/// Options read from the board options file, when it is loaded or modified
+#[derive(Debug, Clone)]
+pub struct BoardOptionsLoadedEvent(pub BoardOptions);
+
+/// Asks for a hint, the answer is sent as a `HintEvent`
+#[derive(Debug, Copy, Clone)]
+pub struct HintRequestEvent;
+
+#[derive(Debug, Clone)]
+pub struct HintEvent {
+    // Hinted covered tile
+    pub coordinates: Coordinates,
+    // Is the tile provably safe, or only the one with the lowest bomb probability?
+    pub safe: bool,
+    pub explanation: String,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct HeatmapToggleEvent;
+
+/// Undoes the last player action or redoes the last undone one
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HistoryEvent {
+    Undo,
+    Redo,
+}
\ No newline at end of file
@@ -0,0 +1,938 @@
+use crate::components::Coordinates;
+use crate::resources::tile::Tile;
+use crate::resources::tile_map::TileMap;
+use crate::resources::{
+    BoardMask, BoardOptionsError, BoardText, BoardTextError, Neighborhood, NoGuessOptions, SafeFirstClick,
+    TileMark, Topology,
+};
+use crate::solver::{generate_no_guess, NoGuessReport};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Headless game generation options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameOptions {
+    pub map_size: (u16, u16),
+    pub bomb_count: u16,
+    pub safe_first_click: SafeFirstClick,
+    // Generation seed, a random one is picked if not set
+    pub seed: Option<u64>,
+    // Only generate boards which can be cleared without guessing from the starting opening.
+    // Without a safe first click the game starts opened on the map center
+    pub no_guess: Option<NoGuessOptions>,
+    // Board shape, replacing 'map_size' when set
+    pub mask: Option<BoardMask>,
+    pub topology: Topology,
+    // Tiles counted by the numbers
+    pub neighborhood: Neighborhood,
+    // Makes the opposite edges of the map adjacent
+    pub wrap: bool,
+    // Bombs a single tile can hold, the numbers count bombs rather than tiles
+    pub max_bombs_per_tile: u8,
+    // Bombs subtracting one from the neighbor numbers, in addition to 'bomb_count'
+    pub negative_bomb_count: u16,
+    // Allows undoing the click which exploded a bomb
+    pub practice: bool,
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        Self {
+            map_size: Default::default(),
+            bomb_count: 0,
+            safe_first_click: Default::default(),
+            seed: None,
+            no_guess: None,
+            mask: None,
+            topology: Default::default(),
+            neighborhood: Default::default(),
+            wrap: false,
+            max_bombs_per_tile: 1,
+            negative_bomb_count: 0,
+            practice: false,
+        }
+    }
+}
+
+/// State of a game
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GameOutcome {
+    // Tiles are still to be revealed
+    Running,
+    // Every non-bomb tile is revealed
+    Won,
+    // A bomb was revealed
+    Lost { exploded: Coordinates },
+}
+
+/// Tile revealed by a player action
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RevealedTile {
+    pub coordinates: Coordinates,
+    // distance in tiles to the tile the action was on, the reveal spreads as a wave
+    pub depth: u32,
+}
+
+/// Player action kinds
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ActionKind {
+    Reveal,
+    Flag,
+    Chord,
+}
+
+/// Player action recorded in the game history, with everything it changed
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Action {
+    pub kind: ActionKind,
+    pub coordinates: Coordinates,
+    // tiles revealed by the action, in the order of the reveal wave
+    pub revealed: Vec<RevealedTile>,
+    // flags of the tile before and after a flag action
+    pub flags: (u8, u8),
+    // game outcome before and after the action
+    pub outcome: (GameOutcome, GameOutcome),
+}
+
+impl Action {
+    /// Did the action explode a bomb?
+    pub fn is_fatal(&self) -> bool {
+        matches!(self.outcome.1, GameOutcome::Lost { .. })
+    }
+}
+
+/// Game state written to a save, the action history isn't kept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSave {
+    pub tile_map: TileMap,
+    pub revealed: Vec<Vec<bool>>,
+    pub flagged: Vec<Vec<u8>>,
+    // bombs waiting for the first reveal to be placed
+    pub pending_bombs: Option<u16>,
+    pub negative_bomb_count: u16,
+    pub safe_first_click: SafeFirstClick,
+    pub seed: u64,
+    pub no_guess: Option<NoGuessOptions>,
+    pub practice: bool,
+    pub undone_explosions: u32,
+}
+
+/// Minesweeper game rules, independent of any rendering. The player actions are ignored once
+/// the game is over, but can be undone
+#[derive(Debug, Clone)]
+pub struct Game {
+    tile_map: TileMap,
+    revealed: Vec<Vec<bool>>,
+    // number of flags on each tile, up to the bombs a tile can hold
+    flagged: Vec<Vec<u8>>,
+    // number of tiles not revealed yet, bombs included
+    covered_count: usize,
+    // bombs waiting for the first reveal to be placed
+    pending_bombs: Option<u16>,
+    // negative bombs placed along with the bombs
+    negative_bomb_count: u16,
+    safe_first_click: SafeFirstClick,
+    // seed used to generate the tile map
+    seed: u64,
+    no_guess: Option<NoGuessOptions>,
+    // outcome of the no guess generation, once the bombs are placed
+    no_guess_report: Option<NoGuessReport>,
+    outcome: GameOutcome,
+    practice: bool,
+    // player actions, the last one first undone
+    history: Vec<Action>,
+    // undone actions, the last one first redone
+    undone: Vec<Action>,
+    // number of bomb explosions undone in practice mode
+    undone_explosions: u32,
+}
+
+impl GameOptions {
+    /// Size of the map, the mask one if any
+    pub fn map_size(&self) -> (u16, u16) {
+        match &self.mask {
+            None => self.map_size,
+            Some(mask) => (mask.width(), mask.height()),
+        }
+    }
+
+    /// Number of tiles of the map, masked out tiles excluded
+    pub fn tile_count(&self) -> u32 {
+        match &self.mask {
+            None => self.map_size.0 as u32 * self.map_size.1 as u32,
+            Some(mask) => mask.tile_count(),
+        }
+    }
+
+    /// Checks that a game can be generated from these options
+    pub fn validate(&self) -> Result<(), BoardOptionsError> {
+        let (width, height) = self.map_size();
+        if self.tile_count() == 0 {
+            return Err(BoardOptionsError::EmptyMap { width, height });
+        }
+        if !self.neighborhood.supports(self.topology) {
+            return Err(BoardOptionsError::UnsupportedNeighborhood {
+                topology: self.topology,
+                neighborhood: self.neighborhood,
+            });
+        }
+        // Hexagon lines and triangle orientations alternate, they must keep alternating across
+        // the wrapped edges
+        let even_lines = match self.topology {
+            Topology::Square => true,
+            Topology::Hexagonal => height % 2 == 0,
+            Topology::Triangular => width % 2 == 0 && height % 2 == 0,
+        };
+        if self.wrap && !even_lines {
+            return Err(BoardOptionsError::UnevenWrap {
+                topology: self.topology,
+                width,
+                height,
+            });
+        }
+        // A number must fit the bombs of all of its neighbors
+        let max_neighbors = self.neighborhood.max_neighbors(self.topology);
+        let max_bombs_per_tile = (u8::MAX as usize / max_neighbors) as u8;
+        if self.max_bombs_per_tile == 0 || self.max_bombs_per_tile > max_bombs_per_tile {
+            return Err(BoardOptionsError::InvalidBombsPerTile {
+                bombs_per_tile: self.max_bombs_per_tile,
+                max: max_bombs_per_tile,
+            });
+        }
+        // The solver behind the no guess generation only knows the classic rules
+        if self.no_guess.is_some() && (self.max_bombs_per_tile > 1 || self.negative_bomb_count > 0)
+        {
+            return Err(BoardOptionsError::UnsupportedNoGuess);
+        }
+        // At least one tile must stay safe, and the whole starting zone in the worst case
+        let safe_tiles = match (self.safe_first_click, &self.no_guess) {
+            (SafeFirstClick::Disabled, None) | (SafeFirstClick::Tile, _) => 1,
+            (SafeFirstClick::Square, _) | (SafeFirstClick::Disabled, Some(_)) => {
+                self.tile_count().min(max_neighbors as u32 + 1)
+            }
+        };
+        let free_tiles = self.tile_count() - safe_tiles;
+        let max = free_tiles * self.max_bombs_per_tile as u32;
+        if self.bomb_count as u32 > max {
+            return Err(BoardOptionsError::TooManyBombs {
+                bomb_count: self.bomb_count,
+                max,
+            });
+        }
+        // Negative bombs can't share a tile with bombs, which may each be alone on their tile
+        let max = free_tiles.saturating_sub(self.bomb_count as u32) * self.max_bombs_per_tile as u32;
+        if self.negative_bomb_count as u32 > max {
+            return Err(BoardOptionsError::TooManyNegativeBombs {
+                negative_bomb_count: self.negative_bomb_count,
+                max,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Game {
+    /// Generates a new game. Without a safe first click the bombs are placed right away,
+    /// otherwise on the first reveal
+    pub fn new(options: &GameOptions) -> Result<Self, BoardOptionsError> {
+        options.validate()?;
+        // Without a seed in the options the game is still reproducible from 'seed()'
+        let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let (width, height) = options.map_size();
+        let mask = options
+            .mask
+            .clone()
+            .unwrap_or_else(|| BoardMask::full(width, height));
+        let mut game = Self {
+            tile_map: TileMap::new(mask, options.topology, options.neighborhood, options.wrap)
+                .with_max_bombs_per_tile(options.max_bombs_per_tile),
+            revealed: vec![vec![false; width as usize]; height as usize],
+            flagged: vec![vec![0; width as usize]; height as usize],
+            covered_count: options.tile_count() as usize,
+            pending_bombs: Some(options.bomb_count),
+            negative_bomb_count: options.negative_bomb_count,
+            safe_first_click: options.safe_first_click,
+            seed,
+            no_guess: options.no_guess.clone(),
+            no_guess_report: None,
+            outcome: GameOutcome::Running,
+            practice: options.practice,
+            history: vec![],
+            undone: vec![],
+            undone_explosions: 0,
+        };
+        if game.safe_first_click == SafeFirstClick::Disabled {
+            match game.no_guess {
+                None => game.place_bombs(&[])?,
+                // A no guess board needs a known opening to start from
+                Some(_) => {
+                    let start = game.tile_map.center();
+                    let safe_zone: Vec<Coordinates> = std::iter::once(start)
+                        .chain(game.tile_map.safe_square_at(start))
+                        .collect();
+                    game.place_bombs_around(start, &safe_zone)?;
+                    game.reveal_area(start);
+                }
+            }
+        }
+        Ok(game)
+    }
+
+    /// Restores a saved game, its outcome is found back from the revealed tiles. `None` if the
+    /// tile map of the save is inconsistent or its grids don't match it
+    pub fn from_save(save: GameSave) -> Option<Self> {
+        if !save.tile_map.is_consistent() {
+            return None;
+        }
+        let size = (save.tile_map.width() as usize, save.tile_map.height() as usize);
+        if !Self::grid_has_size(&save.revealed, size) || !Self::grid_has_size(&save.flagged, size) {
+            return None;
+        }
+        let mut game = Self {
+            tile_map: save.tile_map,
+            revealed: save.revealed,
+            flagged: save.flagged,
+            covered_count: 0,
+            pending_bombs: save.pending_bombs,
+            negative_bomb_count: save.negative_bomb_count,
+            safe_first_click: save.safe_first_click,
+            seed: save.seed,
+            no_guess: save.no_guess,
+            no_guess_report: None,
+            outcome: GameOutcome::Running,
+            practice: save.practice,
+            history: vec![],
+            undone: vec![],
+            undone_explosions: save.undone_explosions,
+        };
+        let coordinates: Vec<Coordinates> = game.tile_map.coordinates().collect();
+        for coords in coordinates {
+            if !game.is_revealed(coords) {
+                game.covered_count += 1;
+            } else if game.tile_map.is_bomb_at(coords) {
+                game.outcome = GameOutcome::Lost { exploded: coords };
+            }
+        }
+        if game.pending_bombs.is_none()
+            && !game.is_over()
+            && game.covered_count == game.tile_map.bomb_tile_count() as usize
+        {
+            game.outcome = GameOutcome::Won;
+        }
+        Some(game)
+    }
+
+    /// Game on a board read from the text format, with its revealed tiles and flags. The other
+    /// rules are taken from 'options', which must be the classic ones of the format
+    pub fn from_board_text(board: BoardText, options: &GameOptions) -> Result<Self, BoardTextError> {
+        if options.topology != Topology::Square
+            || options.neighborhood != Neighborhood::Adjacent
+            || options.wrap
+            || options.max_bombs_per_tile != 1
+            || options.negative_bomb_count != 0
+        {
+            return Err(BoardTextError::UnsupportedRules);
+        }
+        let revealed = board
+            .marks
+            .iter()
+            .map(|line| line.iter().map(|m| *m == TileMark::Revealed).collect())
+            .collect();
+        let flagged = board
+            .marks
+            .iter()
+            .map(|line| line.iter().map(|m| u8::from(*m == TileMark::Flagged)).collect())
+            .collect();
+        let save = GameSave {
+            tile_map: board.tile_map,
+            revealed,
+            flagged,
+            pending_bombs: None,
+            negative_bomb_count: 0,
+            safe_first_click: options.safe_first_click,
+            seed: options.seed.unwrap_or_default(),
+            no_guess: None,
+            practice: options.practice,
+            undone_explosions: 0,
+        };
+        Self::from_save(save).ok_or(BoardTextError::InvalidMarks)
+    }
+
+    /// State of the game to write to a save
+    pub fn to_save(&self) -> GameSave {
+        GameSave {
+            tile_map: self.tile_map.clone(),
+            revealed: self.revealed.clone(),
+            flagged: self.flagged.clone(),
+            pending_bombs: self.pending_bombs,
+            negative_bomb_count: self.negative_bomb_count,
+            safe_first_click: self.safe_first_click,
+            seed: self.seed,
+            no_guess: self.no_guess.clone(),
+            practice: self.practice,
+            undone_explosions: self.undone_explosions,
+        }
+    }
+
+    /// Getter for 'tile_map'
+    pub fn tile_map(&self) -> &TileMap {
+        &self.tile_map
+    }
+
+    /// Revealed grid, indexed by line then column like the tile map
+    pub fn revealed(&self) -> &[Vec<bool>] {
+        &self.revealed
+    }
+
+    /// Flag count grid, indexed by line then column like the tile map
+    pub fn flagged(&self) -> &[Vec<u8>] {
+        &self.flagged
+    }
+
+    /// Getter for 'pending_bombs'
+    pub fn pending_bombs(&self) -> Option<u16> {
+        self.pending_bombs
+    }
+
+    /// Getter for 'safe_first_click'
+    pub fn safe_first_click(&self) -> SafeFirstClick {
+        self.safe_first_click
+    }
+
+    /// Getter for 'seed'
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Getter for 'no_guess_report'
+    pub fn no_guess_report(&self) -> Option<NoGuessReport> {
+        self.no_guess_report
+    }
+
+    /// Getter for 'outcome'
+    pub fn outcome(&self) -> GameOutcome {
+        self.outcome
+    }
+
+    /// Getter for 'practice'
+    pub fn is_practice(&self) -> bool {
+        self.practice
+    }
+
+    /// Number of bomb explosions undone, games with some shouldn't be ranked
+    pub fn undone_explosions(&self) -> u32 {
+        self.undone_explosions
+    }
+
+    /// Player actions which can be undone, the oldest first
+    pub fn history(&self) -> &[Action] {
+        &self.history
+    }
+
+    /// Is the game won or lost?
+    pub fn is_over(&self) -> bool {
+        self.outcome != GameOutcome::Running
+    }
+
+    /// Number of bombs of the game, placed or not
+    pub fn bomb_count(&self) -> u16 {
+        self.pending_bombs
+            .unwrap_or_else(|| self.tile_map.bomb_count())
+    }
+
+    /// Getter for 'negative_bomb_count'
+    pub fn negative_bomb_count(&self) -> u16 {
+        self.negative_bomb_count
+    }
+
+    /// Number of tiles not revealed yet, bombs included
+    pub fn covered_count(&self) -> usize {
+        self.covered_count
+    }
+
+    /// Is the tile at 'coords' revealed? Tiles outside of the board are not
+    pub fn is_revealed(&self, coords: Coordinates) -> bool {
+        Self::grid_at(&self.revealed, coords).unwrap_or(false)
+    }
+
+    /// Is the tile at 'coords' flagged? Tiles outside of the board are not
+    pub fn is_flagged(&self, coords: Coordinates) -> bool {
+        self.flag_count(coords) > 0
+    }
+
+    /// Number of flags on the tile at 'coords'
+    pub fn flag_count(&self, coords: Coordinates) -> u8 {
+        Self::grid_at(&self.flagged, coords).unwrap_or(0)
+    }
+
+    /// Player mark of the tile at 'coords', to write the board as text
+    pub fn tile_mark(&self, coords: Coordinates) -> TileMark {
+        if self.is_revealed(coords) {
+            TileMark::Revealed
+        } else if self.is_flagged(coords) {
+            TileMark::Flagged
+        } else {
+            TileMark::Covered
+        }
+    }
+
+    /// Coordinates kept free of bombs when the first reveal is on 'coords'
+    pub fn first_click_safe_zone(&self, coords: Coordinates) -> Vec<Coordinates> {
+        match self.safe_first_click {
+            SafeFirstClick::Disabled => vec![],
+            SafeFirstClick::Tile => vec![coords],
+            SafeFirstClick::Square => std::iter::once(coords)
+                .chain(self.tile_map.safe_square_at(coords))
+                .collect(),
+        }
+    }
+
+    /// Reveals a covered and unflagged tile, opening the empty area it belongs to and its
+    /// numbered border. Returns the revealed tiles in breadth first order, starting with 'coords'.
+    ///
+    /// With a safe first click the first reveal places the bombs, which fails if they can't fit
+    /// around the safe zone
+    pub fn reveal(&mut self, coords: Coordinates) -> Result<Vec<RevealedTile>, BoardOptionsError> {
+        if !self.can_reveal(coords) {
+            return Ok(vec![]);
+        }
+        if self.pending_bombs.is_some() {
+            let safe_zone = self.first_click_safe_zone(coords);
+            self.place_bombs_around(coords, &safe_zone)?;
+        }
+        let outcome = self.outcome;
+        let revealed = self.reveal_area(coords);
+        self.record(ActionKind::Reveal, coords, &revealed, (0, 0), outcome);
+        Ok(revealed)
+    }
+
+    /// Reveals a tile and its opening without recording it in the history. The bombs must be
+    /// placed
+    fn reveal_area(&mut self, coords: Coordinates) -> Vec<RevealedTile> {
+        if !self.can_reveal(coords) {
+            return vec![];
+        }
+        let mut revealed = vec![];
+        // Tiles are revealed when queued so that each one is queued once
+        self.uncover(coords);
+        let mut queue = VecDeque::from([(coords, 0)]);
+        while let Some((coords, depth)) = queue.pop_front() {
+            revealed.push(RevealedTile {
+                coordinates: coords,
+                depth,
+            });
+            match self.tile_map.tile_at(coords) {
+                Some(Tile::Bomb(_)) | Some(Tile::NegativeBomb(_)) => {
+                    self.outcome = GameOutcome::Lost { exploded: coords };
+                    return revealed;
+                }
+                // Empty tiles propagate the reveal to their neighbors. Numbers stop it, a 0 means
+                // that bombs around the tile cancel out
+                Some(Tile::Empty) => {
+                    let neighbors: Vec<Coordinates> =
+                        self.tile_map.safe_square_at(coords).collect();
+                    for neighbor in neighbors {
+                        if self.can_reveal(neighbor) {
+                            self.uncover(neighbor);
+                            queue.push_back((neighbor, depth + 1));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        if self.covered_count == self.tile_map.bomb_tile_count() as usize {
+            self.outcome = GameOutcome::Won;
+        }
+        revealed
+    }
+
+    /// Adds a flag to a covered tile, or removes them all once it has as many as a tile can hold
+    /// bombs. Returns the new flag count of the tile
+    pub fn cycle_flag(&mut self, coords: Coordinates) -> Option<u8> {
+        if self.is_over() || !self.tile_map.contains(coords) || self.is_revealed(coords) {
+            return None;
+        }
+        let max = self.tile_map.max_bombs_per_tile();
+        let before = self.flag_count(coords);
+        let after = if before >= max { 0 } else { before + 1 };
+        self.flagged[coords.y as usize][coords.x as usize] = after;
+        self.record(ActionKind::Flag, coords, &[], (before, after), self.outcome);
+        Some(after)
+    }
+
+    /// Reveals the neighbors of a revealed bomb neighbor when its count matches the adjacent
+    /// flags. A misplaced flag means a bomb gets revealed and the game is lost.
+    ///
+    /// With negative bombs a number doesn't tell how many bombs are around, so there is no chord
+    pub fn chord(&mut self, coords: Coordinates) -> Vec<RevealedTile> {
+        if !self.is_revealed(coords) || self.negative_bomb_count > 0 {
+            return vec![];
+        }
+        let count = match self.tile_map.tile_at(coords) {
+            Some(Tile::BombNeighbor(v)) => *v,
+            _ => return vec![],
+        };
+        if self.adjacent_flag_count(coords) as i16 != count {
+            return vec![];
+        }
+        let outcome = self.outcome;
+        let neighbors: Vec<Coordinates> = self.tile_map.safe_square_at(coords).collect();
+        // The depths are counted from the chorded tile
+        let mut revealed: Vec<RevealedTile> = neighbors
+            .into_iter()
+            .flat_map(|c| self.reveal_area(c))
+            .map(|tile| RevealedTile {
+                depth: tile.depth + 1,
+                ..tile
+            })
+            .collect();
+        revealed.sort_by_key(|tile| tile.depth);
+        self.record(ActionKind::Chord, coords, &revealed, (0, 0), outcome);
+        revealed
+    }
+
+    /// Undoes the last player action, returning it. The action exploding a bomb can only be
+    /// undone in practice mode, and is then counted in 'undone_explosions'.
+    ///
+    /// The bombs placed by a first click stay in place
+    pub fn undo(&mut self) -> Option<Action> {
+        if self.history.last()?.is_fatal() {
+            if !self.practice {
+                return None;
+            }
+            self.undone_explosions += 1;
+        }
+        let action = self.history.pop()?;
+        for tile in action.revealed.iter() {
+            let coords = tile.coordinates;
+            self.revealed[coords.y as usize][coords.x as usize] = false;
+            self.covered_count += 1;
+        }
+        if action.kind == ActionKind::Flag {
+            let coords = action.coordinates;
+            self.flagged[coords.y as usize][coords.x as usize] = action.flags.0;
+        }
+        self.outcome = action.outcome.0;
+        self.undone.push(action.clone());
+        Some(action)
+    }
+
+    /// Redoes the last undone action, returning it. Any new action clears the undone ones
+    pub fn redo(&mut self) -> Option<Action> {
+        let action = self.undone.pop()?;
+        for tile in action.revealed.iter() {
+            self.uncover(tile.coordinates);
+        }
+        if action.kind == ActionKind::Flag {
+            let coords = action.coordinates;
+            self.flagged[coords.y as usize][coords.x as usize] = action.flags.1;
+        }
+        self.outcome = action.outcome.1;
+        self.history.push(action.clone());
+        Some(action)
+    }
+
+    /// Forgets every action, they can't be undone anymore
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.undone.clear();
+    }
+
+    /// 3BV of the board, meaningless while the bombs are pending
+    pub fn bbbv(&self) -> u32 {
+        self.tile_map.bbbv()
+    }
+
+    /// Part of the board 3BV cleared by the revealed tiles
+    pub fn solved_bbbv(&self) -> u32 {
+        self.tile_map.solved_bbbv(|coords| self.is_revealed(coords))
+    }
+
+    /// Number of flags on the tiles adjacent to 'coords'
+    pub fn adjacent_flag_count(&self, coords: Coordinates) -> usize {
+        self.tile_map
+            .safe_square_at(coords)
+            .map(|c| self.flag_count(c) as usize)
+            .sum()
+    }
+
+    /// Can the tile at 'coords' be revealed by the player?
+    fn can_reveal(&self, coords: Coordinates) -> bool {
+        !self.is_over()
+            && self.tile_map.contains(coords)
+            && !self.is_revealed(coords)
+            && !self.is_flagged(coords)
+    }
+
+    /// Records a player action which changed the game
+    fn record(
+        &mut self,
+        kind: ActionKind,
+        coordinates: Coordinates,
+        revealed: &[RevealedTile],
+        flags: (u8, u8),
+        outcome: GameOutcome,
+    ) {
+        if revealed.is_empty() && flags.0 == flags.1 {
+            return;
+        }
+        self.history.push(Action {
+            kind,
+            coordinates,
+            revealed: revealed.to_vec(),
+            flags,
+            outcome: (outcome, self.outcome),
+        });
+        self.undone.clear();
+    }
+
+    /// Marks a tile as revealed
+    fn uncover(&mut self, coords: Coordinates) {
+        self.revealed[coords.y as usize][coords.x as usize] = true;
+        self.covered_count -= 1;
+    }
+
+    fn grid_has_size<T>(grid: &[Vec<T>], (width, height): (usize, usize)) -> bool {
+        grid.len() == height && grid.iter().all(|line| line.len() == width)
+    }
+
+    fn grid_at<T: Copy>(grid: &[Vec<T>], coords: Coordinates) -> Option<T> {
+        grid.get(coords.y as usize)
+            .and_then(|line| line.get(coords.x as usize))
+            .copied()
+    }
+
+    /// Places the pending bombs anywhere but on 'excluded'
+    fn place_bombs(&mut self, excluded: &[Coordinates]) -> Result<(), BoardOptionsError> {
+        if let Some(bomb_count) = self.pending_bombs {
+            self.tile_map.set_signed_bombs_excluding(
+                bomb_count,
+                self.negative_bomb_count,
+                excluded,
+                self.seed,
+            )?;
+            self.pending_bombs = None;
+        }
+        Ok(())
+    }
+
+    /// Places the pending bombs anywhere but on 'safe_zone', with a board which can be cleared
+    /// from 'start' for no guess games
+    fn place_bombs_around(
+        &mut self,
+        start: Coordinates,
+        safe_zone: &[Coordinates],
+    ) -> Result<(), BoardOptionsError> {
+        let (bomb_count, no_guess) = match (self.pending_bombs, &self.no_guess) {
+            (Some(bomb_count), Some(no_guess)) => (bomb_count, no_guess.clone()),
+            _ => return self.place_bombs(safe_zone),
+        };
+        let report = generate_no_guess(
+            &mut self.tile_map,
+            bomb_count,
+            start,
+            safe_zone,
+            self.seed,
+            &no_guess,
+        )?;
+        self.no_guess_report = Some(report);
+        self.pending_bombs = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::tile_map_with_bombs;
+
+    /// Game with its bombs exactly at 'bombs', nothing revealed
+    fn game(width: u16, height: u16, bombs: &[Coordinates]) -> Game {
+        let options = GameOptions {
+            map_size: (width, height),
+            bomb_count: bombs.len() as u16,
+            seed: Some(0),
+            ..Default::default()
+        };
+        let mut game = Game::new(&options).unwrap();
+        game.tile_map = tile_map_with_bombs(width, height, bombs);
+        game
+    }
+
+    fn at(x: i32, y: i32) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    /// Bombs in the top corners of a 5x3 map, the rest is one opening bordered by numbers
+    fn two_bombs() -> Game {
+        game(5, 3, &[at(0, 0), at(4, 0)])
+    }
+
+    #[test]
+    fn reveal_opens_the_empty_area_and_its_border() {
+        let mut game = two_bombs();
+        let revealed = game.reveal(at(2, 2)).unwrap();
+        assert_eq!(revealed[0], RevealedTile { coordinates: at(2, 2), depth: 0 });
+        assert_eq!(revealed.len(), 13);
+        assert!(revealed.windows(2).all(|w| w[0].depth <= w[1].depth));
+        assert_eq!(game.covered_count(), 2);
+        assert_eq!(game.outcome(), GameOutcome::Won);
+    }
+
+    #[test]
+    fn reveal_of_a_number_only_reveals_it() {
+        let mut game = two_bombs();
+        assert_eq!(game.reveal(at(1, 0)).unwrap().len(), 1);
+        assert_eq!(game.outcome(), GameOutcome::Running);
+        // Revealed and flagged tiles can't be revealed
+        assert!(game.reveal(at(1, 0)).unwrap().is_empty());
+        game.cycle_flag(at(3, 0));
+        assert!(game.reveal(at(3, 0)).unwrap().is_empty());
+        assert_eq!(game.covered_count(), 14);
+    }
+
+    #[test]
+    fn reveal_of_a_bomb_loses() {
+        let mut game = two_bombs();
+        game.reveal(at(0, 0)).unwrap();
+        assert_eq!(game.outcome(), GameOutcome::Lost { exploded: at(0, 0) });
+        assert!(game.is_over());
+        assert!(game.reveal(at(2, 2)).unwrap().is_empty());
+        assert_eq!(game.cycle_flag(at(4, 0)), None);
+    }
+
+    #[test]
+    fn revealing_the_last_safe_tile_wins() {
+        let mut game = game(2, 2, &[at(0, 1)]);
+        game.reveal(at(0, 0)).unwrap();
+        game.reveal(at(1, 0)).unwrap();
+        assert_eq!(game.outcome(), GameOutcome::Running);
+        game.reveal(at(1, 1)).unwrap();
+        assert_eq!(game.outcome(), GameOutcome::Won);
+    }
+
+    #[test]
+    fn chord_reveals_the_neighbors_of_a_satisfied_number() {
+        let mut game = two_bombs();
+        game.reveal(at(1, 0)).unwrap();
+        // Without the flag the number isn't satisfied
+        assert!(game.chord(at(1, 0)).is_empty());
+        game.cycle_flag(at(0, 0));
+        let revealed = game.chord(at(1, 0));
+        assert!(revealed.iter().all(|tile| tile.depth >= 1));
+        assert!(game.is_revealed(at(2, 2)));
+        assert_eq!(game.outcome(), GameOutcome::Won);
+    }
+
+    #[test]
+    fn chord_with_a_misplaced_flag_loses() {
+        let mut game = game(2, 2, &[at(0, 1)]);
+        game.reveal(at(1, 0)).unwrap();
+        game.cycle_flag(at(1, 1));
+        game.chord(at(1, 0));
+        assert_eq!(game.outcome(), GameOutcome::Lost { exploded: at(0, 1) });
+    }
+
+    #[test]
+    fn first_click_is_safe() {
+        for seed in 0..20 {
+            let options = GameOptions {
+                map_size: (9, 9),
+                bomb_count: 70,
+                safe_first_click: SafeFirstClick::Square,
+                seed: Some(seed),
+                ..Default::default()
+            };
+            let mut game = Game::new(&options).unwrap();
+            assert_eq!(game.pending_bombs(), Some(70));
+            game.reveal(at(4, 4)).unwrap();
+            assert_eq!(game.pending_bombs(), None);
+            assert_eq!(game.tile_map().bomb_count(), 70);
+            // The 3x3 square around the click is free, the click opens it
+            assert_eq!(game.tile_map().tile_at(at(4, 4)), Some(&Tile::Empty));
+            assert_eq!(game.outcome(), GameOutcome::Running);
+        }
+    }
+
+    #[test]
+    fn failed_bomb_placement_is_returned() {
+        let options = GameOptions {
+            map_size: (3, 3),
+            safe_first_click: SafeFirstClick::Square,
+            ..Default::default()
+        };
+        let mut game = Game::new(&options).unwrap();
+        // More bombs than the tiles left around the safe zone
+        game.pending_bombs = Some(1);
+        assert!(matches!(
+            game.reveal(at(1, 1)),
+            Err(BoardOptionsError::TooManyBombs { .. })
+        ));
+        assert!(!game.is_revealed(at(1, 1)));
+    }
+
+    #[test]
+    fn undo_and_redo_restore_reveals_and_flags() {
+        let mut game = two_bombs();
+        game.cycle_flag(at(0, 0));
+        game.reveal(at(1, 0)).unwrap();
+        assert_eq!(game.history().len(), 2);
+
+        let action = game.undo().unwrap();
+        assert_eq!(action.kind, ActionKind::Reveal);
+        assert!(!game.is_revealed(at(1, 0)));
+        assert_eq!(game.covered_count(), 15);
+        game.undo();
+        assert!(!game.is_flagged(at(0, 0)));
+        assert_eq!(game.undo(), None);
+
+        game.redo();
+        assert!(game.is_flagged(at(0, 0)));
+        game.redo();
+        assert!(game.is_revealed(at(1, 0)));
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.covered_count(), 14);
+    }
+
+    #[test]
+    fn new_action_clears_the_undone_ones() {
+        let mut game = two_bombs();
+        game.reveal(at(1, 0)).unwrap();
+        game.undo();
+        game.reveal(at(3, 0)).unwrap();
+        assert_eq!(game.redo(), None);
+        assert!(!game.is_revealed(at(1, 0)));
+    }
+
+    #[test]
+    fn explosions_are_only_undone_in_practice() {
+        let mut ranked = two_bombs();
+        ranked.reveal(at(0, 0)).unwrap();
+        assert_eq!(ranked.undo(), None);
+        assert!(ranked.is_over());
+
+        let mut practice = two_bombs();
+        practice.practice = true;
+        practice.reveal(at(0, 0)).unwrap();
+        assert!(practice.undo().unwrap().is_fatal());
+        assert_eq!(practice.outcome(), GameOutcome::Running);
+        assert_eq!(practice.undone_explosions(), 1);
+        practice.redo();
+        assert_eq!(practice.outcome(), GameOutcome::Lost { exploded: at(0, 0) });
+    }
+
+    #[test]
+    fn too_many_bombs_are_refused() {
+        let options = GameOptions {
+            map_size: (3, 3),
+            bomb_count: 10,
+            ..Default::default()
+        };
+        assert!(matches!(
+            Game::new(&options),
+            Err(BoardOptionsError::TooManyBombs { .. })
+        ));
+    }
+}
@@ -0,0 +1,613 @@
+use bevy::log;
+use bevy::prelude::*;
+use crate::components::*;
+use crate::game::Game;
+use crate::resources::tile_map::TileMap;
+use crate::resources::BoardOptions;
+use crate::resources::TileSize;
+use crate::resources::BoardPosition;
+use crate::resources::Topology;
+use crate::resources::BoardOptionsError;
+use crate::solver::NoGuessReport;
+use crate::bounds::Bounds2;
+use crate::resources::tile::Tile;
+use bevy::math::Vec3Swizzles;
+use crate::resources::board::Board;
+use bevy::utils::{AHashExt, HashMap};
+use crate::events::*;
+use crate::systems;
+use bevy::ecs::schedule::StateData;
+use bevy::ecs::system::EntityCommands;
+use crate::resources::{
+    BoardAssets, BoardOptionsAsset, BoardOptionsLoader, GameStats, Heatmap, SaveFile,
+};
+use crate::systems::mark::spawn_flag;
+use crate::systems::BoardSystem;
+
+pub struct BoardPlugin<T> {
+    pub running_state: T,
+    // State pushed when the board is completed, if any
+    pub won_state: Option<T>,
+    // State pushed when a bomb explodes, if any
+    pub lost_state: Option<T>,
+}
+
+/// States pushed by the plugin when a game ends
+struct OutcomeStates<T> {
+    won: Option<T>,
+    lost: Option<T>,
+}
+
+
+impl<T: StateData>  Plugin for BoardPlugin<T> {
+    fn build(&self, app: &mut App) {
+        // When the running states comes into the stack we load a board
+        app
+            .add_system_set(
+            SystemSet::on_enter(self.running_state.clone())
+                .with_system(Self::create_board.chain(Self::board_error_handler)),
+        )
+            .add_system_set(
+                SystemSet::on_update(self.running_state.clone())
+                    .with_system(systems::input::input_handling)
+                    .with_system(systems::uncover::trigger_event_handler)
+                    .with_system(systems::uncover::chord_event_handler)
+                    .with_system(systems::mark::mark_tiles)
+                    .with_system(systems::input::keyboard_input_handling)
+                    .with_system(systems::hint::hint_request_handler)
+                    .with_system(systems::stats::click_counter)
+                    .with_system(systems::heatmap::heatmap_hover),
+            )
+            .add_system_set(
+                SystemSet::on_in_stack_update(self.running_state.clone())
+                    .with_system(systems::input::history_input_handling)
+                    .with_system(
+                        systems::history::history_event_handler.after(BoardSystem::Uncover),
+                    )
+                    .with_system(systems::input::save_input_handling)
+                    .with_system(systems::save::save_event_handler)
+                    .with_system(systems::save::load_event_handler)
+                    .with_system(systems::uncover::uncover_tiles.label(BoardSystem::Uncover))
+                    .with_system(systems::heatmap::heatmap_update.after(BoardSystem::Uncover))
+                    .with_system(systems::stats::stats_update.after(BoardSystem::Uncover))
+                    .with_system(Self::outcome_state_handler),
+            )
+            .add_system_set(
+                SystemSet::on_update(self.running_state.clone()).with_system(
+                    systems::options_file::options_loaded_handler.chain(Self::board_error_handler),
+                ),
+            )
+            // The options file is followed in every state, the next board uses its options
+            .add_system(systems::options_file::options_file_handler)
+            .add_system_to_stage(CoreStage::Last, systems::save::save_on_exit)
+            .add_system_set(SystemSet::on_exit(self.running_state.clone())
+                .with_system(Self::cleanup_board),
+            )
+            .add_event::<TileTriggerEvent>()
+            .add_event::<TileMarkEvent>()
+            .add_event::<TileChordEvent>()
+            .add_event::<TilesRevealedEvent>()
+            .add_event::<BombExplosionEvent>()
+            .add_event::<BoardCompletedEvent>()
+            .add_event::<BoardErrorEvent>()
+            .add_event::<HintRequestEvent>()
+            .add_event::<HintEvent>()
+            .add_event::<HeatmapToggleEvent>()
+            .add_event::<HistoryEvent>()
+            .add_event::<BoardOptionsLoadedEvent>()
+            .add_event::<SaveGameEvent>()
+            .add_event::<LoadGameEvent>()
+            .init_resource::<SaveFile>()
+            .add_asset::<BoardOptionsAsset>()
+            .init_asset_loader::<BoardOptionsLoader>()
+            .init_resource::<Heatmap>()
+            .insert_resource(OutcomeStates {
+                won: self.won_state.clone(),
+                lost: self.lost_state.clone(),
+            });
+
+
+        // app.add_startup_system(Self::create_board)
+        //     .add_system(input_handling)
+        //     .add_system(trigger_event_handler)
+        //     .add_system(uncover_tiles)
+        //     .add_event::<TileTriggerEvent>();
+        // log::info!("Loaded Board Plugin");
+        // #[cfg(feature = "debug")]
+        //     {
+        //         app.register_inspectable::<Coordinates>();
+        //         app.register_inspectable::<BombNeighbor>();
+        //         app.register_inspectable::<Bomb>();
+        //         app.register_inspectable::<Uncover>();
+        //     }
+    }
+}
+
+impl<T: StateData> BoardPlugin<T> {
+    /// System to generate the complete board. No board is created if the options are invalid
+    pub fn create_board(
+        mut commands: Commands,
+        board_options: Option<Res<BoardOptions>>,
+        board_assets: Res<BoardAssets>,
+        window: Option<Res<WindowDescriptor>>,
+    ) -> Result<(), BoardOptionsError> {
+        // if no option is set, use the default one
+        let options = match board_options {
+            None => BoardOptions::default(),
+            Some(o) => o.clone(),
+        };
+        let window_options = match window {
+            None => WindowDescriptor::default(),
+            Some(o) => o.clone(),
+        };
+
+        spawn_board(&mut commands, &options, &board_assets, window_options)
+    }
+
+    /// Reports the board generation errors
+    fn board_error_handler(
+        In(result): In<Result<(), BoardOptionsError>>,
+        mut board_error_ewr: EventWriter<BoardErrorEvent>,
+    ) {
+        if let Err(e) = result {
+            log::error!("Failed to generate the board: {}", e);
+            board_error_ewr.send(BoardErrorEvent(e));
+        }
+    }
+
+    /// Despawns the board. The game is only saved on request or when the app exits, leaving the
+    /// running state for a new game keeps the save file
+    fn cleanup_board(board: Option<Res<Board>>, mut commands: Commands) {
+        // The board is missing if its generation failed
+        if let Some(board) = board {
+            despawn_board(&mut commands, &board);
+        }
+    }
+
+    /// Pushes the configured won/lost state when the game ends, and pops it when the end is
+    /// undone
+    fn outcome_state_handler(
+        board: Option<Res<Board>>,
+        outcome_states: Res<OutcomeStates<T>>,
+        mut state: ResMut<State<T>>,
+        mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+        mut board_completed_evr: EventReader<BoardCompletedEvent>,
+    ) {
+        if let Some(board) = board {
+            let current = Some(state.current().clone());
+            let ended = current == outcome_states.won || current == outcome_states.lost;
+            if ended && !board.game.is_over() {
+                if let Err(e) = state.pop() {
+                    log::error!("Failed to pop game outcome state: {:?}", e);
+                }
+                return;
+            }
+        }
+        // Several events can be sent on the same frame, a single state is pushed
+        let lost = bomb_explosion_evr.iter().count() > 0;
+        let won = board_completed_evr.iter().count() > 0;
+        let next_state = match (lost, won) {
+            (true, _) => &outcome_states.lost,
+            (false, true) => &outcome_states.won,
+            (false, false) => return,
+        };
+        if let Some(next_state) = next_state {
+            if state.current() == next_state {
+                return;
+            }
+            if let Err(e) = state.push(next_state.clone()) {
+                log::error!("Failed to push game outcome state: {:?}", e);
+            }
+        }
+    }
+
+}
+
+/// Generates a game from 'options' and spawns its board
+pub(crate) fn spawn_board(
+    commands: &mut Commands,
+    options: &BoardOptions,
+    board_assets: &BoardAssets,
+    window_options: WindowDescriptor,
+) -> Result<(), BoardOptionsError> {
+    let mut game = match &options.layout {
+        None => Game::new(&options.game_options())?,
+        Some(text) => TileMap::from_text(text)
+            .and_then(|board| Game::from_board_text(board, &options.game_options()))
+            .map_err(BoardOptionsError::InvalidLayout)?,
+    };
+    log::info!("board seed: {}", game.seed());
+    log::info!("board preset: {}", options.game_preset(&game).name());
+    if let Some(report) = game.no_guess_report() {
+        log_no_guess_report(report);
+    }
+    // Before the bombs are placed every tile is empty, the first click does the safe start. A
+    // layout is played as written
+    if options.safe_start && game.pending_bombs().is_none() && options.layout.is_none() {
+        if let Some(start) = first_empty_tile(game.tile_map()) {
+            game.reveal(start)?;
+            // The safe start is part of the board, not a player action
+            game.clear_history();
+        }
+    }
+    #[cfg(feature = "debug")]
+    if game.pending_bombs().is_none() {
+        // Tilemap debugging
+        log::info!("{}", game.tile_map().console_output());
+    }
+    let board = spawn_game_board(commands, game, options, board_assets, window_options);
+    commands.insert_resource(board);
+    commands.insert_resource(GameStats::default());
+    Ok(())
+}
+
+/// Spawns the board entities of 'game', covering its tiles not revealed yet. The returned board
+/// must be inserted as a resource
+pub(crate) fn spawn_game_board(
+    commands: &mut Commands,
+    game: Game,
+    options: &BoardOptions,
+    board_assets: &BoardAssets,
+    window_options: WindowDescriptor,
+) -> Board {
+    let tile_map = game.tile_map();
+
+    // We define the size of our tiles in world space
+    let tile_size = match options.tile_size {
+        TileSize::Fixed(v) => v,
+        TileSize::Adaptive { min, max } => adaptive_tile_size(
+            window_options,
+            (min, max),
+            tile_map.topology().board_size((tile_map.width(), tile_map.height()), 1.),
+        ),
+    };
+
+    let topology = tile_map.topology();
+    let board_size = topology.board_size((tile_map.width(), tile_map.height()), tile_size);
+    log::info!("board size: {}", board_size);
+    // Define the board anchor position (bottom left)
+    let board_position = match options.position {
+        BoardPosition::Centered { offset } => {
+            Vec3::new(-(board_size.x / 2.), -(board_size.y / 2.), 0.) + offset
+        }
+        BoardPosition::Costume(p) => p,
+    };
+
+    // Only square tiles cover the whole board, the background would show between the others
+    let has_holes = tile_map.tile_count() < tile_map.width() as u32 * tile_map.height() as u32
+        || topology != Topology::Square;
+    let mut covered_tiles =
+        HashMap::with_capacity(tile_map.width() as usize * tile_map.height() as usize);
+
+    let board_entity = commands
+        .spawn()
+        .insert(Name::new("Board"))
+        .insert(Transform::from_translation(board_position))
+        .insert(GlobalTransform::default())
+        .with_children(|parent| {
+            // We spawn the board background sprite at the center of the board, since the sprite
+            // pivot is centered. Boards with holes have none
+            if !has_holes {
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: board_assets.board_material.color,
+                            custom_size: Some(board_size),
+                            ..Default::default()
+                        },
+                        texture: board_assets.board_material.texture.clone(),
+                        transform: Transform::from_xyz(board_size.x / 2., board_size.y / 2., 0.),
+                        ..Default::default()
+                    })
+                    .insert(Name::new("Background"));
+            }
+            spawn_tiles(
+                parent,
+                &game,
+                tile_size,
+                options.tile_padding,
+                board_assets,
+                &mut covered_tiles,
+            );
+            // The neighborhood rule is shown above the board
+            parent
+                .spawn_bundle(hud_text_bundle(
+                    format!("Rule: {}", game.tile_map().neighborhood().name()),
+                    board_assets,
+                    Vec3::new(board_size.x / 2., board_size.y + HUD_FONT_SIZE, 1.),
+                ))
+                .insert(Name::new("Rule"));
+        })
+        .id();
+
+    let preset = options.game_preset(&game);
+    Board {
+        game,
+        bounds: Bounds2 {
+            position: board_position.xy(),
+            size: board_size,
+        },
+        tile_size,
+        tile_padding: options.tile_padding,
+        covered_tiles,
+        hints_used: 0,
+        preset,
+        options: options.clone(),
+        entity: board_entity,
+    }
+}
+
+/// Despawns the board entities and removes the resources of its game
+pub(crate) fn despawn_board(commands: &mut Commands, board: &Board) {
+    commands.entity(board.entity).despawn_recursive();
+    commands.remove_resource::<Board>();
+    commands.remove_resource::<GameStats>();
+}
+
+/// Logs whether the no guess generation had to fall back to a board requiring guesses
+pub(crate) fn log_no_guess_report(report: NoGuessReport) {
+    if report.solved {
+        log::info!("No guess board generated in {} attempts", report.attempts);
+    } else {
+        log::warn!(
+            "No board without guesses found in {} attempts, falling back to a random board",
+            report.attempts
+        );
+    }
+}
+
+/// First empty tile of the map, line by line, if any
+fn first_empty_tile(tile_map: &TileMap) -> Option<Coordinates> {
+    tile_map
+        .coordinates()
+        .find(|coords| tile_map.tile_at(*coords) == Some(&Tile::Empty))
+}
+
+/// Computes a tile size that matches the window according to the tile map size
+fn adaptive_tile_size(
+    window: WindowDescriptor,
+    (min, max): (f32, f32), // Tile size constraints
+    unit_size: Vec2, // Board size with tiles of size 1
+) -> f32 {
+    let max_width = window.width / unit_size.x;
+    let max_height = window.height / unit_size.y;
+    max_width.min(max_height).clamp(min, max)
+}
+
+/// Generates the bomb counter text 2D Bundle for a given value
+fn bomb_count_text_bundle(count: i16, board_assets: &BoardAssets, size: f32) -> Text2dBundle {
+    let color = board_assets.bomb_counter_color(count);
+    // Generate a text bundle
+    Text2dBundle {
+        text: Text {
+            sections: vec![TextSection {
+                value: count.to_string(),
+                style: TextStyle {
+                    color,
+                    font: board_assets.bomb_counter_font.clone(),
+                    font_size: size,
+                },
+            }],
+            alignment : TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal: HorizontalAlign::Center,
+            },
+        },
+        transform: Transform::from_xyz(0., 0., 1.),
+        ..Default::default()
+    }
+}
+
+/// Generates the count text 2D Bundle of a stack of bombs or flags, in the bottom right corner
+/// of a sprite of 'size'
+pub(crate) fn stack_count_text_bundle(
+    count: u8,
+    board_assets: &BoardAssets,
+    size: f32,
+) -> Text2dBundle {
+    Text2dBundle {
+        text: Text {
+            sections: vec![TextSection {
+                value: count.to_string(),
+                style: TextStyle {
+                    color: Color::WHITE,
+                    font: board_assets.bomb_counter_font.clone(),
+                    font_size: size / 2.,
+                },
+            }],
+            alignment: TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal: HorizontalAlign::Center,
+            },
+        },
+        transform: Transform::from_xyz(size / 4., -size / 4., 2.),
+        ..Default::default()
+    }
+}
+
+/// Font size of the texts around the board
+const HUD_FONT_SIZE: f32 = 20.;
+
+/// Generates a HUD text 2D Bundle at 'translation' in board space
+fn hud_text_bundle(value: String, board_assets: &BoardAssets, translation: Vec3) -> Text2dBundle {
+    Text2dBundle {
+        text: Text {
+            sections: vec![TextSection {
+                value,
+                style: TextStyle {
+                    color: Color::WHITE,
+                    font: board_assets.bomb_counter_font.clone(),
+                    font_size: HUD_FONT_SIZE,
+                },
+            }],
+            alignment: TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal: HorizontalAlign::Center,
+            },
+        },
+        transform: Transform::from_translation(translation),
+        ..Default::default()
+    }
+}
+
+/// Generates the bomb probability text 2D Bundle for a given probability, estimates are shown
+/// with a '~'
+pub(crate) fn percentage_text_bundle(
+    probability: f64,
+    estimate: bool,
+    board_assets: &BoardAssets,
+    size: f32,
+) -> Text2dBundle {
+    let prefix = if estimate { "~" } else { "" };
+    Text2dBundle {
+        text: Text {
+            sections: vec![TextSection {
+                value: format!("{}{:.0}%", prefix, probability * 100.),
+                style: TextStyle {
+                    color: Color::WHITE,
+                    font: board_assets.bomb_counter_font.clone(),
+                    font_size: size / 2.,
+                },
+            }],
+            alignment: TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal: HorizontalAlign::Center,
+            },
+        },
+        transform: Transform::from_xyz(0., 0., 1.),
+        ..Default::default()
+    }
+}
+
+// what is this Handle part of the code?
+fn spawn_tiles (
+    parent: &mut ChildBuilder,
+    game: &Game,
+    size: f32,
+    padding: f32,
+    board_assets: &BoardAssets,
+    covered_tiles: &mut HashMap<Coordinates, Entity>,
+) {
+    let topology = game.tile_map().topology();
+    // Tiles are drawn as the largest square fitting in their shape
+    let sprite_size = topology.sprite_size(size) - padding;
+    for (y, line) in game.tile_map().iter().enumerate() {
+        for (x, tile) in line.iter().enumerate() {
+            let coordinates = Coordinates {
+                x: x as i32,
+                y: y as i32,
+            };
+            // Masked out tiles are holes in the board
+            if !game.tile_map().contains(coordinates) {
+                continue;
+            }
+            let mut cmd = parent.spawn();
+            cmd.insert_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: board_assets.tile_material.color,
+                    custom_size: Some(Vec2::splat(sprite_size)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(
+                    topology.tile_center(coordinates, size).extend(1.),
+                ),
+                texture: board_assets.tile_material.texture.clone(),
+                ..Default::default()
+            })
+                .insert(Name::new(format!("Tile ({}, {})", x, y)))
+                .insert(coordinates);
+
+            // Add the cover sprites ? Tiles revealed by the game start don't have one, the flags
+            // of a restored game are put back
+            if !game.is_revealed(coordinates) {
+                let flags = game.flag_count(coordinates);
+                cmd.with_children(|parent| {
+                    let mut cover = spawn_cover(parent, sprite_size, board_assets);
+                    if flags > 0 {
+                        cover.insert(Flag).with_children(|parent| {
+                            spawn_flag(parent, flags, sprite_size, board_assets)
+                        });
+                    }
+                    covered_tiles.insert(coordinates, cover.id());
+                });
+            }
+
+            spawn_tile_content(&mut cmd, tile, sprite_size, board_assets);
+        }
+    }
+}
+
+/// Spawns a tile cover sprite of 'size'
+pub(crate) fn spawn_cover<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    size: f32,
+    board_assets: &BoardAssets,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut cover = parent.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            custom_size: Some(Vec2::splat(size)),
+            color: board_assets.covered_tile_material.color,
+            ..Default::default()
+        },
+        transform: Transform::from_xyz(0., 0., 2.),
+        texture: board_assets.covered_tile_material.texture.clone(),
+        ..Default::default()
+    });
+    cover.insert(Name::new("Tile Cover"));
+    cover
+}
+
+/// Adds the bomb or bomb neighbor components and sprites of 'size' matching 'tile' to a tile entity
+pub(crate) fn spawn_tile_content(
+    cmd: &mut EntityCommands,
+    tile: &Tile,
+    size: f32,
+    board_assets: &BoardAssets,
+) {
+    match tile {
+        Tile::Bomb(v) | Tile::NegativeBomb(v) => {
+            let negative = matches!(tile, Tile::NegativeBomb(_));
+            cmd.insert(Bomb {
+                count: *v,
+                negative,
+            });
+            cmd.with_children(|parent| {
+                let sprite = match negative {
+                    false => SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::splat(size)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(0., 0., 1.),
+                        //RFC?
+                        texture: board_assets.bomb_material.texture.clone(),
+                        ..Default::default()
+                    },
+                    true => SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::splat(size)),
+                            color: board_assets.negative_bomb_material.color,
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(0., 0., 1.),
+                        texture: board_assets.negative_bomb_material.texture.clone(),
+                        ..Default::default()
+                    },
+                };
+                parent.spawn_bundle(sprite);
+                // Stacked bombs show their count over the sprite
+                if *v > 1 {
+                    parent.spawn_bundle(stack_count_text_bundle(*v, board_assets, size));
+                }
+            });
+        }
+        // If the tiles is a bomb neighbour we add the matching component and a tezt child
+        Tile::BombNeighbor(v) => {
+            cmd.insert(BombNeighbor { count: *v });
+            cmd.with_children(|parent| {
+                parent.spawn_bundle(bomb_count_text_bundle(*v, board_assets, size));
+            });
+        }
+        Tile::Empty => (),
+    }
+}
\ No newline at end of file
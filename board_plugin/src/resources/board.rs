@@ -1,15 +1,25 @@
 use crate::bounds::Bounds2;
-use crate::{Coordinates, TileMap};
+use crate::components::Coordinates;
+use crate::game::Game;
+use crate::resources::{BoardOptions, BoardPreset};
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 
+/// Board view of a game. Must be used as a resource
 #[derive(Debug)]
 pub struct Board {
-    pub tile_map: TileMap,
+    pub game: Game,
     pub bounds: Bounds2,
     pub tile_size: f32,
+    pub tile_padding: f32,
     // every time we uncover a tile we will remove the entity from our map
     pub covered_tiles: HashMap<Coordinates, Entity>,
+    // number of hints requested during the game
+    pub hints_used: u32,
+    // difficulty the stats of the game are compared within
+    pub preset: BoardPreset,
+    // options the board was created with, saved along with the game
+    pub options: BoardOptions,
     pub entity: Entity,
 }
 
@@ -26,28 +36,23 @@ impl Board {
         }
         // World space to board space
         let coordinates = position - self.bounds.position;
-        Some(Coordinates {
-            x: (coordinates.x / self.tile_size) as u16,
-            y: (coordinates.y / self.tile_size) as u16,
-        })
+        self.game
+            .tile_map()
+            .topology()
+            .tile_at_position(coordinates, self.tile_size)
     }
 
-    // Retrieves a covered tile entity
-    pub fn tile_to_uncover(&self, coords: &Coordinates) -> Option<&Entity> {
-        self.covered_tiles.get(coords)
+    /// Center of the tile at 'coords' in board space
+    pub fn tile_center(&self, coords: Coordinates) -> Vec2 {
+        self.game
+            .tile_map()
+            .topology()
+            .tile_center(coords, self.tile_size)
     }
 
-    // Try to uncover a tile, returning the entity
-    pub fn try_uncover_tile(&mut self, coords: &Coordinates) -> Option<Entity> {
-        self.covered_tiles.remove(coords)
+    /// Size of the tile sprites, which are squares fitting in the tiles
+    pub fn sprite_size(&self) -> f32 {
+        self.game.tile_map().topology().sprite_size(self.tile_size) - self.tile_padding
     }
 
-    // Retrieve the adjacent covered tile entities of 'coord;
-    pub fn adjacent_covered_tiles(&self, coord: Coordinates) -> Vec<Entity> {
-        self.tile_map
-            .safe_square_at(coord)
-            .filter_map(|c| self.covered_tiles.get(&c))
-            .copied()
-            .collect()
-    }
 }
\ No newline at end of file
@@ -32,6 +32,8 @@ pub struct BoardAssets {
     pub bomb_counter_colors: Vec<Color>,
     pub flag_material: SpriteMaterial,
     pub bomb_material: SpriteMaterial,
+    pub negative_bomb_material: SpriteMaterial,
+    pub hint_material: SpriteMaterial,
 }
 
 impl BoardAssets {
@@ -46,9 +48,10 @@ impl BoardAssets {
         ]
     }
 
-    ///Safely retrieves the color matching a bomb counter
-    pub fn bomb_counter_color(&self, counter: u8) -> Color {
-        let counter = counter.saturating_sub(1) as usize;
+    ///Safely retrieves the color matching a bomb counter, negative counters share the color of
+    /// their magnitude
+    pub fn bomb_counter_color(&self, counter: i16) -> Color {
+        let counter = counter.unsigned_abs().saturating_sub(1) as usize;
         match self.bomb_counter_colors.get(counter) {
             Some(c) => *c,
             None => match self.bomb_counter_colors.last() {
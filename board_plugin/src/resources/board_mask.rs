@@ -0,0 +1,184 @@
+use crate::components::Coordinates;
+#[cfg(feature = "plugin")]
+use bevy::render::texture::{Image, ImageType};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+
+/// Board shape, tiles outside of the mask don't exist: they have no sprite, never hold a bomb
+/// and are nobody's neighbor
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BoardMask {
+    width: u16,
+    height: u16,
+    // line by line from the bottom of the board, like the tile map
+    cells: Vec<bool>,
+}
+
+/// Invalid board mask
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum BoardMaskError {
+    // The mask file can't be read
+    Io(String),
+    // The image can't be decoded or has an unsupported pixel format
+    Image(String),
+    // A character of an ASCII mask is neither a tile nor a hole
+    InvalidCharacter { line: usize, column: usize, character: char },
+    // The mask is larger than a tile map can be
+    TooLarge { width: usize, height: usize },
+}
+
+impl Display for BoardMaskError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read the mask: {}", e),
+            Self::Image(e) => write!(f, "invalid mask image: {}", e),
+            Self::InvalidCharacter {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "invalid mask character '{}' at line {}, column {}",
+                character, line, column
+            ),
+            Self::TooLarge { width, height } => {
+                write!(f, "the mask size ({}, {}) is too large", width, height)
+            }
+        }
+    }
+}
+
+impl Error for BoardMaskError {}
+
+impl BoardMask {
+    /// Mask keeping every tile of a 'width' x 'height' rectangle
+    pub fn full(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![true; width as usize * height as usize],
+        }
+    }
+
+    /// Mask from its lines, the first one being the top of the board. Shorter lines are
+    /// completed with holes
+    pub fn from_lines(lines: &[Vec<bool>]) -> Result<Self, BoardMaskError> {
+        let width = lines.iter().map(Vec::len).max().unwrap_or(0);
+        let height = lines.len();
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(BoardMaskError::TooLarge { width, height });
+        }
+        let mut cells = vec![false; width * height];
+        for (i, line) in lines.iter().enumerate() {
+            let y = height - 1 - i;
+            cells[y * width..y * width + line.len()].copy_from_slice(line);
+        }
+        Ok(Self {
+            width: width as u16,
+            height: height as u16,
+            cells,
+        })
+    }
+
+    /// Parses an ASCII mask where '#' is a tile and '.' or ' ' a hole, the first line being
+    /// the top of the board
+    pub fn from_ascii(ascii: &str) -> Result<Self, BoardMaskError> {
+        let lines = ascii
+            .lines()
+            .enumerate()
+            .map(|(line, text)| {
+                text.chars()
+                    .enumerate()
+                    .map(|(column, character)| match character {
+                        '#' => Ok(true),
+                        '.' | ' ' => Ok(false),
+                        _ => Err(BoardMaskError::InvalidCharacter {
+                            line: line + 1,
+                            column: column + 1,
+                            character,
+                        }),
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<Vec<bool>>, BoardMaskError>>()?;
+        Self::from_lines(&lines)
+    }
+
+    /// Loads an ASCII mask file
+    pub fn from_ascii_file(path: impl AsRef<Path>) -> Result<Self, BoardMaskError> {
+        let ascii =
+            std::fs::read_to_string(path).map_err(|e| BoardMaskError::Io(e.to_string()))?;
+        Self::from_ascii(&ascii)
+    }
+
+    /// Mask of an image where the opaque dark pixels are tiles, and the transparent or light
+    /// pixels are holes
+    #[cfg(feature = "plugin")]
+    pub fn from_image(image: &Image) -> Result<Self, BoardMaskError> {
+        let size = image.texture_descriptor.size;
+        let (width, height) = (size.width as usize, size.height as usize);
+        let pixel_count = width * height;
+        if pixel_count == 0 {
+            return Self::from_lines(&[]);
+        }
+        // Decoded images are 8 bit grayscale, grayscale with alpha, or RGBA
+        let pixel_size = image.data.len() / pixel_count;
+        let is_tile = |pixel: &[u8]| match pixel {
+            [luma] => *luma < 128,
+            [luma, alpha] => *luma < 128 && *alpha >= 128,
+            [r, g, b, alpha] => (*r as u16 + *g as u16 + *b as u16) < 3 * 128 && *alpha >= 128,
+            _ => false,
+        };
+        if !matches!(pixel_size, 1 | 2 | 4) || image.data.len() != pixel_count * pixel_size {
+            return Err(BoardMaskError::Image(format!(
+                "unsupported pixel format {:?}",
+                image.texture_descriptor.format
+            )));
+        }
+        let lines: Vec<Vec<bool>> = image
+            .data
+            .chunks(width * pixel_size)
+            .map(|line| line.chunks(pixel_size).map(is_tile).collect())
+            .collect();
+        Self::from_lines(&lines)
+    }
+
+    /// Loads a PNG mask file, see `from_image`
+    #[cfg(feature = "plugin")]
+    pub fn from_png_file(path: impl AsRef<Path>) -> Result<Self, BoardMaskError> {
+        let bytes = std::fs::read(path).map_err(|e| BoardMaskError::Io(e.to_string()))?;
+        let image = Image::from_buffer(&bytes, ImageType::Extension("png"))
+            .map_err(|e| BoardMaskError::Image(e.to_string()))?;
+        Self::from_image(&image)
+    }
+
+    /// Getter for 'width'
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Getter for 'height'
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Is the tile at 'coords' part of the board?
+    pub fn contains(&self, coords: Coordinates) -> bool {
+        (0..self.width as i32).contains(&coords.x)
+            && (0..self.height as i32).contains(&coords.y)
+            && self.cells[coords.y as usize * self.width as usize + coords.x as usize]
+    }
+
+    /// Number of tiles of the board
+    pub fn tile_count(&self) -> u32 {
+        self.cells.iter().filter(|c| **c).count() as u32
+    }
+
+    /// Does the mask hold a cell for each tile of its rectangle? Always true unless it was
+    /// deserialized from a modified file
+    pub fn is_consistent(&self) -> bool {
+        self.cells.len() == self.width as usize * self.height as usize
+    }
+}
@@ -1,5 +1,15 @@
+#[cfg(feature = "plugin")]
+use crate::game::{Game, GameOptions};
+#[cfg(feature = "plugin")]
+use crate::resources::BoardMask;
+#[cfg(feature = "plugin")]
+use crate::resources::BoardPreset;
+use crate::resources::{BoardTextError, Neighborhood, Topology};
+#[cfg(feature = "plugin")]
 use bevy::prelude::Vec3;
 use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
 
 /// Tile size options
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +21,7 @@ pub enum TileSize {
 }
 
 /// Board position customization options
+#[cfg(feature = "plugin")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BoardPosition {
     // Centered board
@@ -18,9 +29,23 @@ pub enum BoardPosition {
     Costume(Vec3),
 }
 
+/// First click protection options
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SafeFirstClick {
+    // Bombs are placed when the board is generated
+    #[default]
+    Disabled,
+    // Bombs are placed on the first click, anywhere but on the clicked tile
+    Tile,
+    // Bombs are placed on the first click, anywhere but on the clicked tile and its neighbors
+    Square,
+}
+
 /// Board generation options. Must be used as a resource
 // Serde used to allow saving option presets and loading them at runtime
+#[cfg(feature = "plugin")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BoardOptions {
     pub map_size: (u16, u16),
     pub bomb_count: u16,
@@ -28,6 +53,200 @@ pub struct BoardOptions {
     pub tile_size: TileSize,
     pub tile_padding: f32,
     pub safe_start: bool,
+    pub safe_first_click: SafeFirstClick,
+    // Generation seed, a random one is picked if not set
+    pub seed: Option<u64>,
+    // Only generate boards which can be cleared without guessing from the starting opening.
+    // Without a safe first click the board starts opened on its center
+    pub no_guess: Option<NoGuessOptions>,
+    // Board shape with holes or any outline, replacing 'map_size' when set
+    pub mask: Option<BoardMask>,
+    // Tile shape, square by default
+    pub topology: Topology,
+    // Tiles counted by the numbers, the adjacent ones by default
+    pub neighborhood: Neighborhood,
+    // Makes the opposite edges of the map adjacent
+    pub wrap: bool,
+    // Bombs a single tile can hold, 1 for the classic rules
+    pub max_bombs_per_tile: u8,
+    // Bombs subtracting one from the neighbor numbers, in addition to 'bomb_count'
+    pub negative_bomb_count: u16,
+    // Allows undoing the click which exploded a bomb, such games shouldn't be ranked
+    pub practice: bool,
+    // Named difficulty, replacing 'map_size' and 'bomb_count' when set
+    pub preset: Option<BoardPreset>,
+    // Hand designed board in the text format, played as written instead of a generated one
+    pub layout: Option<String>,
+}
+
+/// No guess generation options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoGuessOptions {
+    // Layouts generated at most before giving up
+    pub max_attempts: u32,
+    // Time spent at most generating layouts, in milliseconds. The generation runs on the first
+    // click with a safe first click, the frame waits for it
+    pub time_budget_ms: u64,
+}
+
+/// Invalid board generation options
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum BoardOptionsError {
+    // The map has no tile
+    EmptyMap { width: u16, height: u16 },
+    // More bombs than the tiles which can hold one
+    TooManyBombs { bomb_count: u16, max: u32 },
+    // More negative bombs than the tiles the bombs may leave free
+    TooManyNegativeBombs { negative_bomb_count: u16, max: u32 },
+    // The tile pattern of the topology doesn't line up across the wrapped edges
+    UnevenWrap { topology: Topology, width: u16, height: u16 },
+    // The neighborhood rule isn't defined on the topology
+    UnsupportedNeighborhood { topology: Topology, neighborhood: Neighborhood },
+    // A tile must hold at least a bomb, and a number must fit the bombs of all its neighbors
+    InvalidBombsPerTile { bombs_per_tile: u8, max: u8 },
+    // The no guess generation only supports the classic rules
+    UnsupportedNoGuess,
+    // The bombs of an endless board chunk are too sparse or too dense
+    InvalidBombDensity { bombs_per_chunk: u16, min: u16, max: u16 },
+    // The board layout text can't be read or played
+    InvalidLayout(BoardTextError),
+}
+
+impl Display for BoardOptionsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyMap { width, height } => {
+                write!(f, "the map size ({}, {}) has no tile", width, height)
+            }
+            Self::TooManyBombs { bomb_count, max } => write!(
+                f,
+                "{} bombs can't fit on the map, at most {} can be placed",
+                bomb_count, max
+            ),
+            Self::TooManyNegativeBombs {
+                negative_bomb_count,
+                max,
+            } => write!(
+                f,
+                "{} negative bombs can't fit on the map, at most {} can be placed",
+                negative_bomb_count, max
+            ),
+            Self::UnevenWrap {
+                topology,
+                width,
+                height,
+            } => write!(
+                f,
+                "a wrapping {:?} map can't have the size ({}, {})",
+                topology, width, height
+            ),
+            Self::UnsupportedNeighborhood {
+                topology,
+                neighborhood,
+            } => write!(
+                f,
+                "the {} neighborhood can't be used on a {:?} map",
+                neighborhood.name(),
+                topology
+            ),
+            Self::InvalidBombsPerTile {
+                bombs_per_tile,
+                max,
+            } => write!(
+                f,
+                "a tile can't hold up to {} bombs, the limit is between 1 and {}",
+                bombs_per_tile, max
+            ),
+            Self::UnsupportedNoGuess => {
+                write!(f, "no guess boards can only use the classic rules")
+            }
+            Self::InvalidBombDensity {
+                bombs_per_chunk,
+                min,
+                max,
+            } => write!(
+                f,
+                "{} bombs per chunk is not between {} and {}",
+                bombs_per_chunk, min, max
+            ),
+            Self::InvalidLayout(e) => write!(f, "invalid board layout: {}", e),
+        }
+    }
+}
+
+impl Error for BoardOptionsError {}
+
+#[cfg(feature = "plugin")]
+impl BoardOptions {
+    /// Number of tiles of the map
+    pub fn tile_count(&self) -> u32 {
+        self.game_options().tile_count()
+    }
+
+    /// Checks that a board can be generated from these options
+    pub fn validate(&self) -> Result<(), BoardOptionsError> {
+        self.game_options().validate()
+    }
+
+    /// Preset the board is played with, a custom one named after the board size when none is set
+    pub fn current_preset(&self) -> BoardPreset {
+        match &self.preset {
+            Some(preset) => preset.clone(),
+            None => BoardPreset::custom(
+                format!("Custom {}x{}", self.map_size.0, self.map_size.1),
+                self.map_size,
+                self.bomb_count,
+            ),
+        }
+    }
+
+    /// Preset 'game' is played with: the selected one when the game matches it, otherwise a
+    /// custom one named after the game board. Layouts and masks can give another board size
+    pub fn game_preset(&self, game: &Game) -> BoardPreset {
+        let tile_map = game.tile_map();
+        let (width, height) = (tile_map.width(), tile_map.height());
+        let preset = self.current_preset();
+        let is_full = tile_map.tile_count() == width as u32 * height as u32;
+        if is_full
+            && preset.map_size() == (width, height)
+            && preset.bomb_count() == game.bomb_count()
+        {
+            return preset;
+        }
+        let name = if is_full {
+            format!("Custom {}x{}", width, height)
+        } else {
+            format!("Custom {}x{} shaped", width, height)
+        };
+        BoardPreset::custom(name, (width, height), game.bomb_count())
+    }
+
+    /// Options read from an options file, keeping the preset selected while playing if any
+    pub fn merge_file(&self, file: &BoardOptions) -> Self {
+        Self {
+            preset: self.preset.clone().or_else(|| file.preset.clone()),
+            ..file.clone()
+        }
+    }
+
+    /// Options of the game played on the board
+    pub fn game_options(&self) -> GameOptions {
+        let preset = self.current_preset();
+        GameOptions {
+            map_size: preset.map_size(),
+            bomb_count: preset.bomb_count(),
+            safe_first_click: self.safe_first_click,
+            seed: self.seed,
+            no_guess: self.no_guess.clone(),
+            mask: self.mask.clone(),
+            topology: self.topology,
+            neighborhood: self.neighborhood,
+            wrap: self.wrap,
+            max_bombs_per_tile: self.max_bombs_per_tile,
+            negative_bomb_count: self.negative_bomb_count,
+            practice: self.practice,
+        }
+    }
 }
 
 impl Default for TileSize {
@@ -39,6 +258,7 @@ impl Default for TileSize {
     }
 }
 
+#[cfg(feature = "plugin")]
 impl Default for BoardPosition {
     fn default() -> Self {
         Self::Centered {
@@ -47,6 +267,16 @@ impl Default for BoardPosition {
     }
 }
 
+impl Default for NoGuessOptions {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1000,
+            time_budget_ms: 200,
+        }
+    }
+}
+
+#[cfg(feature = "plugin")]
 impl Default for BoardOptions {
     fn default() -> Self {
         Self {
@@ -56,6 +286,68 @@ impl Default for BoardOptions {
             tile_size: Default::default(),
             tile_padding: 0.,
             safe_start: false,
+            safe_first_click: Default::default(),
+            seed: None,
+            no_guess: None,
+            mask: None,
+            topology: Default::default(),
+            neighborhood: Default::default(),
+            wrap: false,
+            max_bombs_per_tile: 1,
+            negative_bomb_count: 0,
+            practice: false,
+            preset: None,
+            layout: None,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "plugin"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_options_keep_the_selected_preset() {
+        let current = BoardOptions {
+            preset: Some(BoardPreset::Expert),
+            ..Default::default()
+        };
+        let file = BoardOptions {
+            map_size: (8, 8),
+            bomb_count: 10,
+            tile_padding: 2.,
+            ..Default::default()
+        };
+        let merged = current.merge_file(&file);
+        assert_eq!(merged.preset, Some(BoardPreset::Expert));
+        assert_eq!(merged.map_size, (8, 8));
+        assert_eq!(merged.tile_padding, 2.);
+        // Without a selected preset the one of the file is used
+        let file = BoardOptions {
+            preset: Some(BoardPreset::Beginner),
+            ..Default::default()
+        };
+        let merged = BoardOptions::default().merge_file(&file);
+        assert_eq!(merged.current_preset(), BoardPreset::Beginner);
+    }
+
+    #[test]
+    fn game_preset_follows_the_generated_board() {
+        let options = BoardOptions {
+            preset: Some(BoardPreset::Beginner),
+            ..Default::default()
+        };
+        let game = Game::new(&options.game_options()).unwrap();
+        assert_eq!(options.game_preset(&game), BoardPreset::Beginner);
+        // A mask replaces the preset size
+        let masked = BoardOptions {
+            mask: Some(BoardMask::full(12, 12)),
+            ..options.clone()
+        };
+        let game = Game::new(&masked.game_options()).unwrap();
+        assert_eq!(
+            masked.game_preset(&game),
+            BoardPreset::custom("Custom 12x12", (12, 12), 10)
+        );
+    }
+}
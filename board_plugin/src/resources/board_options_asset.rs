@@ -0,0 +1,45 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::Handle;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use crate::resources::BoardOptions;
+
+/// Board options read from a `.board.ron` or `.board.json` asset file
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "e1f4233c-1dfc-4551-849a-fe9cbd61da95"]
+pub struct BoardOptionsAsset(pub BoardOptions);
+
+/// Options file the board is generated from, the board is regenerated when it changes on disk.
+/// Must be used as a resource
+#[derive(Debug, Clone)]
+pub struct BoardOptionsFile(pub Handle<BoardOptionsAsset>);
+
+/// Loads board options files, the fields missing from a file keep their default value
+#[derive(Debug, Default)]
+pub struct BoardOptionsLoader;
+
+impl AssetLoader for BoardOptionsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let is_json = load_context
+                .path()
+                .extension()
+                .map_or(false, |ext| ext.eq_ignore_ascii_case("json"));
+            let options: BoardOptions = if is_json {
+                serde_json::from_slice(bytes)?
+            } else {
+                ron::de::from_bytes(bytes)?
+            };
+            load_context.set_default_asset(LoadedAsset::new(BoardOptionsAsset(options)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["board.ron", "board.json"]
+    }
+}
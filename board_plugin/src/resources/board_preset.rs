@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+/// Named board size and bomb count. Games are only compared with games of the same preset
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum BoardPreset {
+    // 9x9 with 10 bombs
+    Beginner,
+    // 16x16 with 40 bombs
+    Intermediate,
+    // 30x16 with 99 bombs
+    Expert,
+    // User defined preset
+    Custom {
+        name: String,
+        map_size: (u16, u16),
+        bomb_count: u16,
+    },
+}
+
+impl BoardPreset {
+    /// The classic difficulties, from the easiest
+    pub const STANDARD: [BoardPreset; 3] = [Self::Beginner, Self::Intermediate, Self::Expert];
+
+    /// Builds a user defined preset
+    pub fn custom(name: impl Into<String>, map_size: (u16, u16), bomb_count: u16) -> Self {
+        Self::Custom {
+            name: name.into(),
+            map_size,
+            bomb_count,
+        }
+    }
+
+    /// Name of the preset, as shown to the player
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Beginner => "Beginner",
+            Self::Intermediate => "Intermediate",
+            Self::Expert => "Expert",
+            Self::Custom { name, .. } => name,
+        }
+    }
+
+    /// Width and height of the board
+    pub fn map_size(&self) -> (u16, u16) {
+        match self {
+            Self::Beginner => (9, 9),
+            Self::Intermediate => (16, 16),
+            Self::Expert => (30, 16),
+            Self::Custom { map_size, .. } => *map_size,
+        }
+    }
+
+    /// Number of bombs of the board
+    pub fn bomb_count(&self) -> u16 {
+        match self {
+            Self::Beginner => 10,
+            Self::Intermediate => 40,
+            Self::Expert => 99,
+            Self::Custom { bomb_count, .. } => *bomb_count,
+        }
+    }
+}
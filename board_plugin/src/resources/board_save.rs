@@ -0,0 +1,190 @@
+use crate::game::GameSave;
+use crate::resources::BoardOptions;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Version of the save format written by this build
+pub const SAVE_VERSION: u32 = 1;
+
+/// In progress game written to a save file, with the options its board is rebuilt from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardSave {
+    // format version, saves of another version are refused
+    pub version: u32,
+    pub options: BoardOptions,
+    pub game: GameSave,
+    // time played until the save
+    pub elapsed: Duration,
+    pub hints_used: u32,
+}
+
+/// Path of the save file, `save.ron` in the working directory by default. Must be used as a
+/// resource
+#[derive(Debug, Clone)]
+pub struct SaveFile(pub PathBuf);
+
+impl Default for SaveFile {
+    fn default() -> Self {
+        Self(PathBuf::from("save.ron"))
+    }
+}
+
+/// Save file which can't be written or loaded
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SaveError {
+    // The save file can't be read or written
+    Io(String),
+    // The save file isn't a valid save
+    Format(String),
+    // The save was written by a build using another format
+    UnsupportedVersion { version: u32 },
+    // The saved grids don't match the saved tile map
+    InvalidGame,
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to access the save file: {}", e),
+            Self::Format(e) => write!(f, "invalid save file: {}", e),
+            Self::UnsupportedVersion { version } => write!(
+                f,
+                "save format version {} is not supported, expected {}",
+                version, SAVE_VERSION
+            ),
+            Self::InvalidGame => write!(f, "the saved game doesn't match its tile map"),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+/// Version field of any save, read before the rest of the save
+#[derive(Deserialize)]
+struct SaveVersion {
+    version: u32,
+}
+
+impl BoardSave {
+    /// Reads a save file, refusing the saves of other format versions
+    pub fn read(path: &Path) -> Result<Self, SaveError> {
+        let text = fs::read_to_string(path).map_err(|e| SaveError::Io(e.to_string()))?;
+        // Saves of other versions may not parse as the current format
+        let SaveVersion { version } =
+            ron::from_str(&text).map_err(|e| SaveError::Format(e.to_string()))?;
+        if version != SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion { version });
+        }
+        ron::from_str(&text).map_err(|e| SaveError::Format(e.to_string()))
+    }
+
+    /// Writes the save file, replacing any previous one
+    pub fn write(&self, path: &Path) -> Result<(), SaveError> {
+        let text = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|e| SaveError::Format(e.to_string()))?;
+        fs::write(path, text).map_err(|e| SaveError::Io(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Coordinates;
+    use crate::game::{Game, GameOptions};
+
+    /// Save of a game in progress, with a revealed opening and a flag
+    fn board_save() -> BoardSave {
+        let options = GameOptions {
+            map_size: (9, 9),
+            bomb_count: 10,
+            seed: Some(3),
+            ..Default::default()
+        };
+        let mut game = Game::new(&options).unwrap();
+        game.reveal(Coordinates { x: 4, y: 4 }).unwrap();
+        let covered = game
+            .tile_map()
+            .coordinates()
+            .find(|c| !game.is_revealed(*c))
+            .unwrap();
+        game.cycle_flag(covered);
+        assert!(!game.is_over());
+        BoardSave {
+            version: SAVE_VERSION,
+            options: BoardOptions::default(),
+            game: game.to_save(),
+            elapsed: Duration::from_secs(42),
+            hints_used: 1,
+        }
+    }
+
+    /// Save file path unique to a test
+    fn path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("board_save_{}_{}.ron", name, std::process::id()))
+    }
+
+    #[test]
+    fn save_round_trip() {
+        let save = board_save();
+        let path = path("round_trip");
+        save.write(&path).unwrap();
+        let read = BoardSave::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read.elapsed, save.elapsed);
+        assert_eq!(read.hints_used, save.hints_used);
+        assert_eq!(
+            ron::to_string(&read.game).unwrap(),
+            ron::to_string(&save.game).unwrap()
+        );
+        assert!(Game::from_save(read.game).is_some());
+    }
+
+    #[test]
+    fn other_versions_are_refused() {
+        let save = BoardSave {
+            version: SAVE_VERSION + 1,
+            ..board_save()
+        };
+        let path = path("other_version");
+        save.write(&path).unwrap();
+        let read = BoardSave::read(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            read.unwrap_err(),
+            SaveError::UnsupportedVersion {
+                version: SAVE_VERSION + 1
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_files_are_refused() {
+        let path = path("invalid");
+        fs::write(&path, "(version: 1, game: ())").unwrap();
+        let read = BoardSave::read(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(read, Err(SaveError::Format(_))));
+        assert!(matches!(BoardSave::read(&path), Err(SaveError::Io(_))));
+    }
+
+    #[test]
+    fn inconsistent_games_are_refused() {
+        let mut save = board_save().game;
+        save.revealed.pop();
+        assert!(Game::from_save(save).is_none());
+        // Tile maps which can't come from a game
+        let text = ron::to_string(&board_save().game).unwrap();
+        assert!(text.starts_with("(tile_map:(bomb_count:10,"));
+        for corrupted in [
+            text.replacen("bomb_count:10", "bomb_count:11", 1),
+            text.replacen("width:9", "width:8", 1),
+        ] {
+            let save: GameSave = ron::from_str(&corrupted).unwrap();
+            assert!(Game::from_save(save).is_none());
+        }
+    }
+}
@@ -0,0 +1,409 @@
+//! Plain text board format, to check boards into tests, paste them into bug reports or design
+//! them by hand. Each line is a line of tiles, the first one being the top of the board, with
+//! one character per tile:
+//!
+//! - `.` a revealed empty tile, `1` to `8` a revealed number, `*` a revealed bomb
+//! - `#` a covered tile, `x` a covered bomb
+//! - `f` a flagged tile, `F` a flagged bomb
+//! - ` ` a hole, shorter lines are completed with holes
+//!
+//! An optional first line `board <width>x<height> <bombs>` is checked against the tiles. Only
+//! the classic rules on square tiles can be written: one bomb per tile, the 8 adjacent tiles
+//! as neighbors and no wrapping.
+use crate::components::Coordinates;
+use crate::resources::tile::Tile;
+use crate::resources::tile_map::TileMap;
+use crate::resources::{BoardMask, BoardMaskError, Neighborhood, Topology};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// First word of the optional header line
+const HEADER: &str = "board";
+
+/// Player state of a tile
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TileMark {
+    Covered,
+    Revealed,
+    Flagged,
+}
+
+/// Board read from the text format
+#[derive(Debug, Clone)]
+pub struct BoardText {
+    pub tile_map: TileMap,
+    // marks of the tiles, indexed by line then column like the tile map. Holes are covered
+    pub marks: Vec<Vec<TileMark>>,
+}
+
+/// Board which can't be read or written in the text format
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum BoardTextError {
+    // A character is neither a tile nor a hole
+    InvalidCharacter { line: usize, column: usize, character: char },
+    // The header line isn't `board <width>x<height> <bombs>`
+    InvalidHeader { line: String },
+    // The header size doesn't match the tile lines
+    SizeMismatch { header: (u16, u16), found: (u16, u16) },
+    // The header bomb count doesn't match the bombs of the tiles
+    BombCountMismatch { header: u16, found: usize },
+    // More bombs than a tile map can count
+    TooManyBombs { found: usize },
+    // A revealed number doesn't match the bombs around it
+    WrongNumber { line: usize, column: usize, number: u8, expected: i16 },
+    // The tile lines don't make a valid board
+    Mask(BoardMaskError),
+    // The board has no tile
+    EmptyMap { width: u16, height: u16 },
+    // The map rules can't be written with single digit numbers
+    UnsupportedRules,
+    // The marks don't have the size of the tile map
+    InvalidMarks,
+}
+
+impl Display for BoardTextError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCharacter {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "invalid board character '{}' at line {}, column {}",
+                character, line, column
+            ),
+            Self::InvalidHeader { line } => write!(
+                f,
+                "invalid header '{}', expected '{} <width>x<height> <bombs>'",
+                line, HEADER
+            ),
+            Self::SizeMismatch { header, found } => write!(
+                f,
+                "the header size {:?} doesn't match the board size {:?}",
+                header, found
+            ),
+            Self::BombCountMismatch { header, found } => write!(
+                f,
+                "the header bomb count {} doesn't match the {} bombs of the board",
+                header, found
+            ),
+            Self::TooManyBombs { found } => {
+                write!(f, "{} bombs are more than a board can hold", found)
+            }
+            Self::WrongNumber {
+                line,
+                column,
+                number,
+                expected,
+            } => write!(
+                f,
+                "the number {} at line {}, column {} should be {}",
+                number, line, column, expected
+            ),
+            Self::Mask(e) => write!(f, "invalid board shape: {}", e),
+            Self::EmptyMap { width, height } => {
+                write!(f, "the map size ({}, {}) has no tile", width, height)
+            }
+            Self::UnsupportedRules => write!(
+                f,
+                "only the classic rules on square tiles are supported by the text format"
+            ),
+            Self::InvalidMarks => write!(f, "the tile marks don't match the tile map"),
+        }
+    }
+}
+
+impl Error for BoardTextError {}
+
+/// Tile parsed from a character
+struct TextTile {
+    bomb: bool,
+    mark: TileMark,
+    // number of a revealed bomb neighbor, 0 for an empty tile
+    number: Option<u8>,
+}
+
+impl TileMap {
+    /// Writes the map in the text format, with the marks given by 'mark'
+    pub fn to_text(
+        &self,
+        mark: impl Fn(Coordinates) -> TileMark,
+    ) -> Result<String, BoardTextError> {
+        if !self.has_standard_rules()
+            || self.topology() != Topology::Square
+            || self.neighborhood() != Neighborhood::Adjacent
+            || self.wrap()
+        {
+            return Err(BoardTextError::UnsupportedRules);
+        }
+        let mut text = format!(
+            "{} {}x{} {}\n",
+            HEADER,
+            self.width(),
+            self.height(),
+            self.bomb_count()
+        );
+        for y in (0..self.height() as i32).rev() {
+            for x in 0..self.width() as i32 {
+                let coords = Coordinates { x, y };
+                let character = match self.tile_at(coords) {
+                    Some(tile) if self.contains(coords) => text_character(tile, mark(coords)),
+                    _ => ' ',
+                };
+                text.push(character);
+            }
+            text.push('\n');
+        }
+        Ok(text)
+    }
+
+    /// Reads a board in the text format, see the module documentation
+    pub fn from_text(text: &str) -> Result<BoardText, BoardTextError> {
+        let lines: Vec<&str> = text.lines().collect();
+        let header = match lines.first() {
+            Some(line) if line.split_whitespace().next() == Some(HEADER) => {
+                Some(parse_header(line)?)
+            }
+            _ => None,
+        };
+        // Line numbers of the errors count the header
+        let skipped = header.is_some() as usize;
+        let first_line = skipped + 1;
+        let tiles = lines[skipped..]
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(column, character)| {
+                        parse_character(character).ok_or(BoardTextError::InvalidCharacter {
+                            line: first_line + i,
+                            column: column + 1,
+                            character,
+                        })
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<Vec<Option<TextTile>>>, BoardTextError>>()?;
+
+        let shape: Vec<Vec<bool>> = tiles
+            .iter()
+            .map(|line| line.iter().map(Option::is_some).collect())
+            .collect();
+        let mask = BoardMask::from_lines(&shape).map_err(BoardTextError::Mask)?;
+        let (width, height) = (mask.width(), mask.height());
+        if mask.tile_count() == 0 {
+            return Err(BoardTextError::EmptyMap { width, height });
+        }
+        if let Some((size, bomb_count)) = header {
+            if size != (width, height) {
+                return Err(BoardTextError::SizeMismatch {
+                    header: size,
+                    found: (width, height),
+                });
+            }
+            let found = tiles.iter().flatten().flatten().filter(|t| t.bomb).count();
+            if found != bomb_count as usize {
+                return Err(BoardTextError::BombCountMismatch {
+                    header: bomb_count,
+                    found,
+                });
+            }
+        }
+
+        let mut tile_map = TileMap::new(mask, Topology::Square, Neighborhood::Adjacent, false);
+        let mut marks = vec![vec![TileMark::Covered; width as usize]; height as usize];
+        let mut bombs = vec![];
+        for (i, line) in tiles.iter().enumerate() {
+            let y = height as usize - 1 - i;
+            for (x, tile) in line.iter().enumerate() {
+                if let Some(tile) = tile {
+                    marks[y][x] = tile.mark;
+                    if tile.bomb {
+                        bombs.push(Coordinates {
+                            x: x as i32,
+                            y: y as i32,
+                        });
+                    }
+                }
+            }
+        }
+        if bombs.len() > u16::MAX as usize {
+            return Err(BoardTextError::TooManyBombs { found: bombs.len() });
+        }
+        tile_map.set_bombs_at(&bombs);
+
+        // The revealed numbers are only checked, the tile map computes them from the bombs
+        for (i, line) in tiles.iter().enumerate() {
+            let y = height as i32 - 1 - i as i32;
+            for (x, tile) in line.iter().enumerate() {
+                let number = match tile.as_ref().and_then(|t| t.number) {
+                    Some(n) => n,
+                    None => continue,
+                };
+                let expected = tile_map.bomb_count_at(Coordinates { x: x as i32, y });
+                if number as i16 != expected {
+                    return Err(BoardTextError::WrongNumber {
+                        line: first_line + i,
+                        column: x + 1,
+                        number,
+                        expected,
+                    });
+                }
+            }
+        }
+        Ok(BoardText { tile_map, marks })
+    }
+}
+
+/// Parses `board <width>x<height> <bombs>`
+fn parse_header(line: &str) -> Result<((u16, u16), u16), BoardTextError> {
+    let error = || BoardTextError::InvalidHeader {
+        line: line.to_string(),
+    };
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (size, bombs) = match words.as_slice() {
+        [_, size, bombs] => (*size, *bombs),
+        _ => return Err(error()),
+    };
+    let (width, height) = size.split_once('x').ok_or_else(error)?;
+    let parse = |s: &str| s.parse::<u16>().map_err(|_| error());
+    Ok(((parse(width)?, parse(height)?), parse(bombs)?))
+}
+
+/// Tile of a character, `None` inside for a hole and `None` for an invalid character
+fn parse_character(character: char) -> Option<Option<TextTile>> {
+    let tile = |bomb, mark, number| Some(Some(TextTile { bomb, mark, number }));
+    match character {
+        ' ' => Some(None),
+        '.' => tile(false, TileMark::Revealed, Some(0)),
+        '1'..='8' => tile(false, TileMark::Revealed, character.to_digit(10).map(|d| d as u8)),
+        '*' => tile(true, TileMark::Revealed, None),
+        '#' => tile(false, TileMark::Covered, None),
+        'x' => tile(true, TileMark::Covered, None),
+        'f' => tile(false, TileMark::Flagged, None),
+        'F' => tile(true, TileMark::Flagged, None),
+        _ => None,
+    }
+}
+
+/// Character of a tile with the classic rules
+fn text_character(tile: &Tile, mark: TileMark) -> char {
+    match (mark, tile.is_bomb()) {
+        (TileMark::Covered, false) => '#',
+        (TileMark::Covered, true) => 'x',
+        (TileMark::Flagged, false) => 'f',
+        (TileMark::Flagged, true) => 'F',
+        (TileMark::Revealed, true) => '*',
+        (TileMark::Revealed, false) => match tile {
+            Tile::BombNeighbor(v) => char::from_digit(*v as u32, 10).unwrap_or('?'),
+            _ => '.',
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, GameOptions};
+
+    const BOARD: &str = "board 5x3 2\n.1x1#\n.12f#\n  1F#\n";
+
+    #[test]
+    fn text_round_trip() {
+        let board = TileMap::from_text(BOARD).unwrap();
+        let marks = board.marks.clone();
+        let text = board
+            .tile_map
+            .to_text(|c| marks[c.y as usize][c.x as usize])
+            .unwrap();
+        assert_eq!(text, BOARD);
+        // The header is optional
+        let without_header = TileMap::from_text(&BOARD[12..]).unwrap();
+        assert_eq!(without_header.marks, marks);
+    }
+
+    #[test]
+    fn game_round_trip() {
+        let board = TileMap::from_text(BOARD).unwrap();
+        let game = Game::from_board_text(board, &GameOptions::default()).unwrap();
+        assert_eq!(game.flag_count(Coordinates { x: 3, y: 0 }), 1);
+        assert_eq!(game.tile_map().to_text(|c| game.tile_mark(c)).unwrap(), BOARD);
+    }
+
+    #[test]
+    fn invalid_characters_are_refused() {
+        assert_eq!(
+            TileMap::from_text("##\n#?\n").unwrap_err(),
+            BoardTextError::InvalidCharacter {
+                line: 2,
+                column: 2,
+                character: '?',
+            }
+        );
+    }
+
+    #[test]
+    fn wrong_numbers_are_refused() {
+        assert_eq!(
+            TileMap::from_text("##2\n##x\n").unwrap_err(),
+            BoardTextError::WrongNumber {
+                line: 1,
+                column: 3,
+                number: 2,
+                expected: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn header_must_match_the_tiles() {
+        assert_eq!(
+            TileMap::from_text("board 3x2 1\n#x\n##\n").unwrap_err(),
+            BoardTextError::SizeMismatch {
+                header: (3, 2),
+                found: (2, 2),
+            }
+        );
+        assert_eq!(
+            TileMap::from_text("board 2x2 2\n#x\n##\n").unwrap_err(),
+            BoardTextError::BombCountMismatch {
+                header: 2,
+                found: 1,
+            }
+        );
+        for header in ["board", "board 2x2", "board 2*2 1", "board 2x2 -1"] {
+            assert_eq!(
+                TileMap::from_text(&format!("{}\n#x\n##\n", header)).unwrap_err(),
+                BoardTextError::InvalidHeader {
+                    line: header.to_string(),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn empty_boards_are_refused() {
+        for text in ["", "\n\n", "   \n", "board 0x0 0\n"] {
+            assert!(
+                matches!(TileMap::from_text(text), Err(BoardTextError::EmptyMap { .. })),
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn other_rules_are_refused() {
+        let options = GameOptions {
+            wrap: true,
+            ..Default::default()
+        };
+        let board = TileMap::from_text(BOARD).unwrap();
+        assert_eq!(
+            Game::from_board_text(board, &options).unwrap_err(),
+            BoardTextError::UnsupportedRules
+        );
+    }
+}
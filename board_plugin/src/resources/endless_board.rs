@@ -0,0 +1,57 @@
+use crate::components::Coordinates;
+use crate::endless::{ChunkCoordinates, EndlessGame};
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Spawned chunk of an endless board
+#[derive(Debug)]
+pub struct ChunkView {
+    pub entity: Entity,
+    // every time we uncover a tile we will remove the entity from our map
+    pub covered_tiles: HashMap<Coordinates, Entity>,
+}
+
+/// Endless board view of an endless game, only the chunks around the camera are spawned. The
+/// origin tile has its bottom left corner on the world origin. Must be used as a resource
+#[derive(Debug)]
+pub struct EndlessBoard {
+    pub game: EndlessGame,
+    pub tile_size: f32,
+    pub tile_padding: f32,
+    // spawned chunks, partitioning the covered tiles
+    pub chunks: HashMap<ChunkCoordinates, ChunkView>,
+    pub entity: Entity,
+}
+
+impl EndlessBoard {
+    /// Translates a mouse position to board coordinates, seen from 'camera'
+    pub fn mouse_position(&self, window: &Window, camera: &Transform, position: Vec2) -> Coordinates {
+        // Window to world space
+        let window_size = Vec2::new(window.width(), window.height());
+        let position = position - window_size / 2. + camera.translation.xy();
+        self.world_coordinates(position)
+    }
+
+    /// Coordinates of the tile at 'position' in world space
+    pub fn world_coordinates(&self, position: Vec2) -> Coordinates {
+        let position = (position / self.tile_size).floor();
+        Coordinates {
+            x: position.x as i32,
+            y: position.y as i32,
+        }
+    }
+
+    /// Size of the tile sprites
+    pub fn sprite_size(&self) -> f32 {
+        self.tile_size - self.tile_padding
+    }
+
+    /// Cover entity of the tile at 'coords', if it is covered and its chunk spawned
+    pub fn cover(&self, coords: Coordinates) -> Option<Entity> {
+        self.chunks
+            .get(&ChunkCoordinates::of(coords))
+            .and_then(|chunk| chunk.covered_tiles.get(&coords))
+            .copied()
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// Statistics of the current game, as compared by competitive players. Must be used as a resource
+#[derive(Debug, Clone, Default)]
+pub struct GameStats {
+    // time played, from the first reveal until the game ends
+    pub elapsed: Duration,
+    // is the clock started?
+    pub started: bool,
+    pub left_clicks: u32,
+    pub right_clicks: u32,
+    pub chord_clicks: u32,
+    // minimum number of reveals clearing the board, known once the bombs are placed
+    pub bbbv: u32,
+    // part of the 3BV cleared so far
+    pub solved_bbbv: u32,
+}
+
+impl GameStats {
+    /// Number of clicks of every kind
+    pub fn clicks(&self) -> u32 {
+        self.left_clicks + self.right_clicks + self.chord_clicks
+    }
+
+    /// 3BV cleared per second
+    pub fn bbbv_per_second(&self) -> f32 {
+        let seconds = self.elapsed.as_secs_f32();
+        if seconds > 0. {
+            self.solved_bbbv as f32 / seconds
+        } else {
+            0.
+        }
+    }
+
+    /// Index of efficiency, the 3BV cleared per click
+    pub fn ioe(&self) -> f32 {
+        match self.clicks() {
+            0 => 0.,
+            clicks => self.solved_bbbv as f32 / clicks as f32,
+        }
+    }
+
+    /// Percentage of the 3BV cleared
+    pub fn completion(&self) -> f32 {
+        match self.bbbv {
+            0 => 0.,
+            bbbv => self.solved_bbbv as f32 * 100. / bbbv as f32,
+        }
+    }
+}
+
+impl Display for GameStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "time: {:.2}s, clicks: {} ({} left, {} right, {} chords), 3BV: {}/{}, 3BV/s: {:.2}, IOE: {:.2}, completion: {:.0}%",
+            self.elapsed.as_secs_f32(),
+            self.clicks(),
+            self.left_clicks,
+            self.right_clicks,
+            self.chord_clicks,
+            self.solved_bbbv,
+            self.bbbv,
+            self.bbbv_per_second(),
+            self.ioe(),
+            self.completion()
+        )
+    }
+}
@@ -0,0 +1,9 @@
+use crate::solver::BombProbabilities;
+
+/// Bomb probability overlay state. Must be used as a resource
+#[derive(Debug, Default)]
+pub struct Heatmap {
+    pub enabled: bool,
+    // Probabilities of the current board, kept up to date while enabled
+    pub probabilities: Option<BombProbabilities>,
+}
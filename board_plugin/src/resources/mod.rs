@@ -1,8 +1,39 @@
-pub(crate) mod tile;
-pub(crate) mod tile_map;
+pub mod tile;
+pub mod tile_map;
 pub use board_options::*;
 
 mod board_options;
+mod board_mask;
+pub use board_mask::*;
+mod topology;
+pub use topology::*;
+mod neighborhood;
+pub use neighborhood::*;
+mod board_preset;
+pub use board_preset::*;
+mod board_text;
+pub use board_text::*;
+#[cfg(feature = "plugin")]
 pub mod board;
+#[cfg(feature = "plugin")]
+pub mod endless_board;
+#[cfg(feature = "plugin")]
 mod board_assets;
-pub use board_assets::*;
\ No newline at end of file
+#[cfg(feature = "plugin")]
+pub use board_assets::*;
+#[cfg(feature = "plugin")]
+mod heatmap;
+#[cfg(feature = "plugin")]
+pub use heatmap::*;
+#[cfg(feature = "plugin")]
+mod board_options_asset;
+#[cfg(feature = "plugin")]
+pub use board_options_asset::*;
+#[cfg(feature = "plugin")]
+mod board_save;
+#[cfg(feature = "plugin")]
+pub use board_save::*;
+#[cfg(feature = "plugin")]
+mod game_stats;
+#[cfg(feature = "plugin")]
+pub use game_stats::*;
@@ -0,0 +1,98 @@
+use crate::components::Coordinates;
+use crate::resources::Topology;
+use serde::{Deserialize, Serialize};
+
+/// Rule deciding which tiles a number counts the bombs of. The reveal of empty tiles and the
+/// chords spread to the same tiles
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Neighborhood {
+    // Tiles touching the tile, as defined by the topology
+    #[default]
+    Adjacent,
+    // The 4 tiles sharing a side, square topology only
+    Orthogonal,
+    // The 8 tiles a knight's move away, square topology only
+    KnightMove,
+    // The 24 other tiles of the 5x5 square around the tile, square topology only
+    Radius2,
+}
+
+impl Neighborhood {
+    /// Name of the rule, as shown to the player
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Adjacent => "Adjacent",
+            Self::Orthogonal => "Orthogonal",
+            Self::KnightMove => "Knight's move",
+            Self::Radius2 => "Radius 2",
+        }
+    }
+
+    /// Can the rule be used with 'topology'?
+    pub fn supports(&self, topology: Topology) -> bool {
+        *self == Self::Adjacent || topology == Topology::Square
+    }
+
+    /// Delta coordinates of the neighbors of the tile at 'coords'
+    pub fn deltas(&self, topology: Topology, coords: Coordinates) -> &'static [(i8, i8)] {
+        match self {
+            Self::Adjacent => topology.neighbor_deltas(coords),
+            Self::Orthogonal => &ORTHOGONAL_COORDINATES,
+            Self::KnightMove => &KNIGHT_MOVE_COORDINATES,
+            Self::Radius2 => &RADIUS_2_COORDINATES,
+        }
+    }
+
+    /// Maximum number of neighbors of a tile
+    pub fn max_neighbors(&self, topology: Topology) -> usize {
+        match self {
+            Self::Adjacent => topology.max_neighbors(),
+            Self::Orthogonal => ORTHOGONAL_COORDINATES.len(),
+            Self::KnightMove => KNIGHT_MOVE_COORDINATES.len(),
+            Self::Radius2 => RADIUS_2_COORDINATES.len(),
+        }
+    }
+}
+
+/// Delta coordinates for the 4 tiles sharing a side
+const ORTHOGONAL_COORDINATES: [(i8, i8); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Delta coordinates for the 8 knight's moves
+const KNIGHT_MOVE_COORDINATES: [(i8, i8); 8] = [
+    (-1, -2),
+    (1, -2),
+    (-2, -1),
+    (2, -1),
+    (-2, 1),
+    (2, 1),
+    (-1, 2),
+    (1, 2),
+];
+
+/// Delta coordinates for the 5x5 square around a tile
+const RADIUS_2_COORDINATES: [(i8, i8); 24] = [
+    (-2, -2),
+    (-1, -2),
+    (0, -2),
+    (1, -2),
+    (2, -2),
+    (-2, -1),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (2, -1),
+    (-2, 0),
+    (-1, 0),
+    (1, 0),
+    (2, 0),
+    (-2, 1),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (2, 1),
+    (-2, 2),
+    (-1, 2),
+    (0, 2),
+    (1, 2),
+    (2, 2),
+];
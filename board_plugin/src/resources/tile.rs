@@ -1,21 +1,41 @@
 #[cfg(feature = "debug")]
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 
 /// Enum describing a Minesweeper tile
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Tile {
-    // Is a bomb
-    Bomb,
-    // Is a bomb neighbor
-    BombNeighbor(u8),
-    // Empty tile
+    // Holds bombs, more than one when stacked
+    Bomb(u8),
+    // Holds negative bombs, which subtract from the neighbor numbers
+    NegativeBomb(u8),
+    // Is a bomb neighbor. The bombs around it can cancel out to 0
+    BombNeighbor(i16),
+    // Empty tile, without any bomb around it
     Empty,
 }
 
 impl Tile {
     // Is the tile a bomb?
     pub const fn is_bomb(&self) -> bool {
-        matches!(self, Self::Bomb)
+        matches!(self, Self::Bomb(_) | Self::NegativeBomb(_))
+    }
+
+    // Number of bombs held by the tile, negative or not
+    pub const fn bombs(&self) -> u8 {
+        match self {
+            Self::Bomb(v) | Self::NegativeBomb(v) => *v,
+            _ => 0,
+        }
+    }
+
+    // Value the tile adds to the numbers of its neighbors
+    pub const fn bomb_value(&self) -> i16 {
+        match self {
+            Self::Bomb(v) => *v as i16,
+            Self::NegativeBomb(v) => -(*v as i16),
+            _ => 0,
+        }
     }
 
     #[cfg(feature = "debug")]
@@ -23,15 +43,24 @@ impl Tile {
         format!(
             "{}",
             match self {
-                Tile::Bomb => "*".bright_red(),
+                Tile::Bomb(1) => "*".bright_red(),
+                // stacks show their count, '+' past 9 to keep the grid aligned
+                Tile::Bomb(v) if *v > 9 => "+".white().on_red(),
+                Tile::Bomb(v) => v.to_string().white().on_red(),
+                Tile::NegativeBomb(1) => "-".bright_blue(),
+                Tile::NegativeBomb(v) if *v > 9 => "+".white().on_blue(),
+                Tile::NegativeBomb(v) => v.to_string().white().on_blue(),
                 Tile::BombNeighbor(v) => match v {
+                    0 => "0".dimmed(),
                     1 => "1".cyan(),
                     2 => "2".green(),
                     3 => "3".yellow(),
+                    // negative numbers show their magnitude
+                    v if *v < 0 => v.unsigned_abs().to_string().magenta(),
                     _ => v.to_string().red(),
                 },
                 Tile::Empty => " ".normal(),
             }
         )
     }
-}
\ No newline at end of file
+}
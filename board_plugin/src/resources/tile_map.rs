@@ -1,44 +1,99 @@
 use crate::resources:: tile::Tile;
+#[cfg(feature = "debug")]
+use colored::Colorize;
 use std::ops::{Deref, DerefMut};
 use crate::components::Coordinates;
-use rand::{thread_rng, Rng};
+use crate::rng::SeededRng;
+use crate::resources::{BoardMask, BoardOptionsError, Neighborhood, Topology};
+use serde::{Deserialize, Serialize};
 
 /// Base tile map
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TileMap {
+    // number of bombs, stacked ones included
     bomb_count: u16,
+    // number of negative bombs, stacked ones included
+    negative_bomb_count: u16,
+    // number of tiles holding bombs
+    bomb_tile_count: u32,
+    // bombs a single tile can hold
+    max_bombs_per_tile: u8,
     height: u16,
     width: u16,
+    // tiles outside of the mask are holes in the board
+    mask: BoardMask,
+    topology: Topology,
+    neighborhood: Neighborhood,
+    // are the opposite edges adjacent?
+    wrap: bool,
     map: Vec<Vec<Tile>>,
 }
 
 impl TileMap {
-    /// Generates an empty map
+    /// Generates an empty map of square tiles
     pub fn empty(width: u16, height: u16) -> Self {
+        Self::new(
+            BoardMask::full(width, height),
+            Topology::Square,
+            Neighborhood::Adjacent,
+            false,
+        )
+    }
+
+    /// Generates an empty map with the shape of 'mask', the tiles of 'topology' and the
+    /// numbers counting the 'neighborhood' bombs. With 'wrap' the left and right edges are
+    /// adjacent, as well as the top and bottom ones
+    pub fn new(
+        mask: BoardMask,
+        topology: Topology,
+        neighborhood: Neighborhood,
+        wrap: bool,
+    ) -> Self {
+        let (width, height) = (mask.width(), mask.height());
         let map = (0..height)
-            .into_iter()
-            .map(|_| (0..width).into_iter().map(|_| Tile::Empty).collect())
+            .map(|_| (0..width).map(|_| Tile::Empty).collect())
             .collect();
         Self {
             bomb_count: 0,
+            negative_bomb_count: 0,
+            bomb_tile_count: 0,
+            max_bombs_per_tile: 1,
             height,
             width,
+            mask,
+            topology,
+            neighborhood,
+            wrap,
             map
         }
     }
 
+    /// Lets a tile hold up to 'max' bombs, the numbers then count bombs rather than tiles
+    pub fn with_max_bombs_per_tile(mut self, max: u8) -> Self {
+        self.max_bombs_per_tile = max.max(1);
+        self
+    }
+
     #[cfg(feature = "debug")]
     pub fn console_output(&self) -> String {
         let mut buffer = format!(
-            "Map ({}, {}) with {} bombs:\n",
-            self.width, self.height, self.bomb_count
+            "Map ({}, {}) with {} bombs and {} negative bombs:\n",
+            self.width, self.height, self.bomb_count, self.negative_bomb_count
         );
-        let line: String = (0..=(self.width + 1)).into_iter().map(|_| '-').collect();
+        let line: String = (0..=(self.width + 1)).map(|_| '-').collect();
         buffer = format!("{}{}\n", buffer, line);
-        for line in self.iter().rev() {
+        for (y, line) in self.iter().enumerate().rev() {
             buffer = format!("{}|", buffer);
-            for tile in line.iter() {
-                buffer = format!("{}{}", buffer, tile.console_output());
+            for (x, tile) in line.iter().enumerate() {
+                let coords = Coordinates {
+                    x: x as i32,
+                    y: y as i32,
+                };
+                if self.contains(coords) {
+                    buffer = format!("{}{}", buffer, tile.console_output());
+                } else {
+                    buffer = format!("{}{}", buffer, "#".dimmed());
+                }
             }
             buffer = format!("{}|\n", buffer);
         }
@@ -60,70 +115,358 @@ impl TileMap {
         self.bomb_count
     }
 
+    /// Getter for 'negative_bomb_count'
+    pub fn negative_bomb_count(&self) -> u16 {
+        self.negative_bomb_count
+    }
+
+    /// Getter for 'bomb_tile_count'
+    pub fn bomb_tile_count(&self) -> u32 {
+        self.bomb_tile_count
+    }
+
+    /// Getter for 'max_bombs_per_tile'
+    pub fn max_bombs_per_tile(&self) -> u8 {
+        self.max_bombs_per_tile
+    }
+
+    /// Are the bombs placed with the classic rules, which the solver relies on?
+    pub fn has_standard_rules(&self) -> bool {
+        self.max_bombs_per_tile == 1 && self.negative_bomb_count == 0
+    }
+
+    /// Getter for 'mask'
+    pub fn mask(&self) -> &BoardMask {
+        &self.mask
+    }
+
+    /// Getter for 'topology'
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Getter for 'neighborhood'
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    /// Getter for 'wrap'
+    pub fn wrap(&self) -> bool {
+        self.wrap
+    }
+
+    /// Is the tile at 'coordinates' part of the board? Out of bounds and masked out tiles are not
+    pub fn contains(&self, coordinates: Coordinates) -> bool {
+        self.mask.contains(coordinates)
+    }
+
+    /// Number of tiles of the board, masked out tiles excluded
+    pub fn tile_count(&self) -> u32 {
+        self.mask.tile_count()
+    }
+
+    /// Coordinates of the board tile closest to the center
+    pub fn center(&self) -> Coordinates {
+        let center = Coordinates {
+            x: self.width as i32 / 2,
+            y: self.height as i32 / 2,
+        };
+        if self.contains(center) {
+            return center;
+        }
+        let distance = |c: &Coordinates| {
+            let dx = c.x - center.x;
+            let dy = c.y - center.y;
+            dx * dx + dy * dy
+        };
+        self.coordinates().min_by_key(distance).unwrap_or(center)
+    }
+
+    /// Coordinates of every board tile, line by line
+    pub fn coordinates(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        (0..self.height as i32)
+            .flat_map(move |y| (0..self.width as i32).map(move |x| Coordinates { x, y }))
+            .filter(move |coords| self.contains(*coords))
+    }
+
+    /// Neighbors of 'coordinates' which are part of the board
     // NOTE: the type returned will implement the Iterator type, and that's all I'm being told
     // NOTE: it's essentially syntactic sugar, but pretty sweet for implementation (don't have to
     // NOTE: kick it out to another struct or anything like that
     pub fn safe_square_at(&self, coordinates: Coordinates) -> impl Iterator<Item = Coordinates> {
-        SQUARE_COORDINATES
-            .iter()
-            .copied()
-            .map(move |tuple| coordinates + tuple)
+        let deltas = self.neighborhood.deltas(self.topology, coordinates);
+        let mut neighbors = Vec::with_capacity(deltas.len());
+        for delta in deltas {
+            let neighbor = match self.offset(coordinates, *delta) {
+                Some(c) => c,
+                None => continue,
+            };
+            // Wrapping around a small map can reach a tile twice, or the tile itself
+            if neighbor != coordinates && self.contains(neighbor) && !neighbors.contains(&neighbor) {
+                neighbors.push(neighbor);
+            }
+        }
+        neighbors.into_iter()
+    }
+
+    /// Coordinates at 'delta' from 'coordinates', wrapping around the edges if enabled
+    fn offset(&self, coordinates: Coordinates, (dx, dy): (i8, i8)) -> Option<Coordinates> {
+        let shift = |value: i32, delta: i8, size: u16| {
+            let value = value + delta as i32;
+            if self.wrap {
+                Some(value.rem_euclid(size as i32))
+            } else if (0..size as i32).contains(&value) {
+                Some(value)
+            } else {
+                None
+            }
+        };
+        Some(Coordinates {
+            x: shift(coordinates.x, dx, self.width)?,
+            y: shift(coordinates.y, dy, self.height)?,
+        })
+    }
+
+    /// Retrieves the tile at a specific coordinate, if part of the board
+    pub fn tile_at(&self, coordinates: Coordinates) -> Option<&Tile> {
+        if !self.contains(coordinates) {
+            return None;
+        }
+        self.map
+            .get(coordinates.y as usize)
+            .and_then(|line| line.get(coordinates.x as usize))
     }
 
     /// Check to see if a bomb is at a specific coordinate
     pub fn is_bomb_at(&self, coordinates: Coordinates) -> bool {
-        // bounds checking
-        if coordinates.x >= self.width || coordinates.y >= self.height {
-            return false;
-        };
-        self.map[coordinates.y as usize][coordinates.x as usize].is_bomb()
+        self.tile_at(coordinates).map_or(false, Tile::is_bomb)
+    }
+
+    /// Number of bombs held by the tile at a specific coordinate
+    pub fn bombs_at(&self, coordinates: Coordinates) -> u8 {
+        self.tile_at(coordinates).map_or(0, Tile::bombs)
     }
 
-    /// Return the number of bombs around a specific coordinate
-    pub fn bomb_count_at(&self, coordinates: Coordinates) -> u8 {
+    /// Return the number of bombs around a specific coordinate, stacked bombs all count and
+    /// negative bombs are subtracted
+    pub fn bomb_count_at(&self, coordinates: Coordinates) -> i16 {
         if self.is_bomb_at(coordinates) {
             return 0;
         }
-        let res = self
-            .safe_square_at(coordinates)
-            .filter(|coord| self.is_bomb_at(*coord))
+        self.safe_square_at(coordinates)
+            .filter_map(|coord| self.tile_at(coord))
+            .map(Tile::bomb_value)
+            .sum()
+    }
+
+    /// Is there a bomb around a specific coordinate, even if the bombs cancel out?
+    pub fn has_bomb_neighbor(&self, coordinates: Coordinates) -> bool {
+        self.safe_square_at(coordinates)
+            .any(|coord| self.is_bomb_at(coord))
+    }
+
+    /// 3BV of the map, the minimum number of reveals clearing it: one per opening, plus one per
+    /// number no opening reveals
+    pub fn bbbv(&self) -> u32 {
+        self.solved_bbbv(|_| true)
+    }
+
+    /// Part of the 3BV already cleared: the openings with a 'revealed' empty tile, and the
+    /// 'revealed' numbers no opening reveals
+    pub fn solved_bbbv(&self, revealed: impl Fn(Coordinates) -> bool) -> u32 {
+        let width = self.width as usize;
+        let index = |coords: Coordinates| coords.y as usize * width + coords.x as usize;
+        // tiles belonging to an opening, its numbered border included
+        let mut opened = vec![false; width * self.height as usize];
+        let mut solved = 0;
+        for coords in self.coordinates() {
+            if opened[index(coords)] || self.tile_at(coords) != Some(&Tile::Empty) {
+                continue;
+            }
+            // Revealing any empty tile of an opening reveals the whole opening
+            let mut cleared = false;
+            opened[index(coords)] = true;
+            let mut stack = vec![coords];
+            while let Some(coords) = stack.pop() {
+                cleared |= revealed(coords);
+                for neighbor in self.safe_square_at(coords) {
+                    if opened[index(neighbor)] {
+                        continue;
+                    }
+                    opened[index(neighbor)] = true;
+                    if self.tile_at(neighbor) == Some(&Tile::Empty) {
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            if cleared {
+                solved += 1;
+            }
+        }
+        let isolated = self
+            .coordinates()
+            .filter(|coords| !opened[index(*coords)] && !self.is_bomb_at(*coords))
+            .filter(|coords| revealed(*coords))
             .count();
-        res as u8
+        solved + isolated as u32
     }
 
-    /// Places bombs and bomb neighbor tiles
-    pub fn set_bombs(&mut self, bomb_count: u16) {
+    /// Places bombs and bomb neighbor tiles. The same 'seed' always gives the same map
+    pub fn set_bombs(&mut self, bomb_count: u16, seed: u64) -> Result<(), BoardOptionsError> {
+        self.set_bombs_excluding(bomb_count, &[], seed)
+    }
+
+    /// Places bombs and bomb neighbor tiles, leaving the 'excluded' coordinates free of bombs
+    pub fn set_bombs_excluding(
+        &mut self,
+        bomb_count: u16,
+        excluded: &[Coordinates],
+        seed: u64,
+    ) -> Result<(), BoardOptionsError> {
+        self.set_signed_bombs_excluding(bomb_count, 0, excluded, seed)
+    }
+
+    /// Places bombs, negative bombs and bomb neighbor tiles, leaving the 'excluded' coordinates
+    /// free of bombs. A tile only holds one kind of bomb
+    pub fn set_signed_bombs_excluding(
+        &mut self,
+        bomb_count: u16,
+        negative_bomb_count: u16,
+        excluded: &[Coordinates],
+        seed: u64,
+    ) -> Result<(), BoardOptionsError> {
+        // A slot for every bomb a tile can hold, in a stable order so a seed always gives the
+        // same map
+        let max_bombs = self.max_bombs_per_tile as usize;
+        let mut candidates: Vec<Coordinates> = self
+            .coordinates()
+            .filter(|coords| !excluded.contains(coords))
+            .flat_map(|coords| std::iter::repeat(coords).take(max_bombs))
+            .collect();
+        if bomb_count as usize > candidates.len() {
+            return Err(BoardOptionsError::TooManyBombs {
+                bomb_count,
+                max: candidates.len() as u32,
+            });
+        }
         self.bomb_count = bomb_count;
-        let mut remaining_bombs = bomb_count;
-        let mut rng = thread_rng();
-        // Place bombs
-        while remaining_bombs > 0 {
-            let (x,y) = (
-                rng.gen_range(0..self.width) as usize,
-                rng.gen_range(0..self.height) as usize,
-            );
-            // don't allow a bomb placement to be wasted
-            if let Tile::Empty = self[y][x] {
-                self[y][x] = Tile::Bomb;
-                remaining_bombs -= 1;
+        self.negative_bomb_count = negative_bomb_count;
+        self.bomb_tile_count = 0;
+        let mut rng = SeededRng::new(seed);
+        // Place bombs with a partial Fisher-Yates shuffle, each draw picks an untouched slot
+        for i in 0..bomb_count as usize {
+            let j = i + rng.gen_below((candidates.len() - i) as u64) as usize;
+            candidates.swap(i, j);
+            self.add_bomb(candidates[i], Tile::Bomb);
+        }
+        if negative_bomb_count > 0 {
+            // Negative bombs go in the slots left, on the tiles without bombs
+            let mut candidates: Vec<Coordinates> = candidates[bomb_count as usize..]
+                .iter()
+                .copied()
+                .filter(|coords| !self.is_bomb_at(*coords))
+                .collect();
+            if negative_bomb_count as usize > candidates.len() {
+                return Err(BoardOptionsError::TooManyNegativeBombs {
+                    negative_bomb_count,
+                    max: candidates.len() as u32,
+                });
+            }
+            for i in 0..negative_bomb_count as usize {
+                let j = i + rng.gen_below((candidates.len() - i) as u64) as usize;
+                candidates.swap(i, j);
+                self.add_bomb(candidates[i], Tile::NegativeBomb);
+            }
+        }
+        self.set_numbers();
+        Ok(())
+    }
+
+    /// Places a bomb on each of the 'bombs' coordinates of an empty map and the bomb neighbor
+    /// tiles, for hand designed maps
+    pub fn set_bombs_at(&mut self, bombs: &[Coordinates]) {
+        for coords in bombs {
+            if self.contains(*coords) && !self.is_bomb_at(*coords) {
+                self.add_bomb(*coords, Tile::Bomb);
+                self.bomb_count += 1;
+            }
+        }
+        self.set_numbers();
+    }
+
+    /// Place bomb neighbors; if a cell is not a bomb, but is neighboring a bomb, update it
+    /// else continue. Bombs cancelling out still make a number, only tiles without any bomb
+    /// around them are empty
+    fn set_numbers(&mut self) {
+        let coordinates: Vec<Coordinates> = self.coordinates().collect();
+        for coords in coordinates {
+            if self.is_bomb_at(coords) || !self.has_bomb_neighbor(coords) {
+                continue;
             }
+            let num = self.bomb_count_at(coords);
+            let tile = &mut self[coords.y as usize][coords.x as usize];
+            *tile = Tile::BombNeighbor(num);
         }
-        // Place bomb neighbors; if a cell is not a bomb, but is neighboring a bomb, update it
-        // else continue
-        for y in 0..self.height {
-            for x in 0..self.width {
+    }
+
+    /// Do the tiles, the bomb counts and the mask of the map agree? Always true unless the map
+    /// was deserialized from a modified file
+    pub fn is_consistent(&self) -> bool {
+        let (width, height) = (self.width as usize, self.height as usize);
+        if !self.mask.is_consistent()
+            || (self.mask.width(), self.mask.height()) != (self.width, self.height)
+            || self.max_bombs_per_tile == 0
+            || self.map.len() != height
+            || self.map.iter().any(|line| line.len() != width)
+        {
+            return false;
+        }
+        let (mut bombs, mut negative_bombs, mut bomb_tiles) = (0u32, 0u32, 0u32);
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
                 let coords = Coordinates { x, y };
-                if self.is_bomb_at(coords) {
+                let tile = self.map[y as usize][x as usize];
+                if !self.contains(coords) {
+                    if tile != Tile::Empty {
+                        return false;
+                    }
                     continue;
                 }
-                let num = self.bomb_count_at(coords);
-                if num == 0 {
-                    continue;
+                match tile {
+                    Tile::Bomb(v) | Tile::NegativeBomb(v)
+                        if v == 0 || v > self.max_bombs_per_tile =>
+                    {
+                        return false
+                    }
+                    Tile::Bomb(v) => bombs += v as u32,
+                    Tile::NegativeBomb(v) => negative_bombs += v as u32,
+                    // Numbers are the ones the bombs around make
+                    Tile::BombNeighbor(v) => {
+                        if !self.has_bomb_neighbor(coords) || v != self.bomb_count_at(coords) {
+                            return false;
+                        }
+                    }
+                    Tile::Empty => {
+                        if self.has_bomb_neighbor(coords) {
+                            return false;
+                        }
+                    }
                 }
-                let tile = &mut self[y as usize][x as usize];
-                *tile = Tile::BombNeighbor(num);
+                bomb_tiles += tile.is_bomb() as u32;
             }
         }
+        bombs == self.bomb_count as u32
+            && negative_bombs == self.negative_bomb_count as u32
+            && bomb_tiles == self.bomb_tile_count
+    }
+
+    /// Adds a bomb to the stack of the tile at 'coords', 'kind' building the tile of a stack
+    fn add_bomb(&mut self, coords: Coordinates, kind: fn(u8) -> Tile) {
+        let bombs = self.bombs_at(coords);
+        if bombs == 0 {
+            self.bomb_tile_count += 1;
+        }
+        self[coords.y as usize][coords.x as usize] = kind(bombs + 1);
     }
 }
 
@@ -141,22 +484,34 @@ impl DerefMut for TileMap {
     }
 }
 
-/// Delta coordinates for all 8 square neightbors
-const SQUARE_COORDINATES: [(i8, i8); 8] = [
-    // Bottom Left
-    (-1, -1),
-    // Bottom
-    (0, -1),
-    // Bottom Right
-    (1, -1),
-    // Left
-    (-1, 0),
-    // Right
-    (1, 0),
-    // Top Left
-    (-1, 1),
-    // Top
-    (0, 1),
-    // Top right
-    (1, 1),
-];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::tile_map_with_bombs;
+
+    fn at(x: i32, y: i32) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    #[test]
+    fn bbbv_counts_openings_once() {
+        assert_eq!(tile_map_with_bombs(5, 1, &[at(2, 0)]).bbbv(), 2);
+        assert_eq!(tile_map_with_bombs(5, 3, &[at(0, 0), at(4, 0)]).bbbv(), 1);
+    }
+
+    #[test]
+    fn bbbv_counts_each_isolated_number() {
+        assert_eq!(tile_map_with_bombs(3, 3, &[at(1, 1)]).bbbv(), 8);
+    }
+
+    #[test]
+    fn solved_bbbv_counts_the_cleared_part() {
+        let openings = tile_map_with_bombs(5, 1, &[at(2, 0)]);
+        assert_eq!(openings.solved_bbbv(|_| false), 0);
+        // A number of the opening border doesn't clear the opening
+        assert_eq!(openings.solved_bbbv(|c| c == at(1, 0)), 0);
+        assert_eq!(openings.solved_bbbv(|c| c == at(0, 0)), 1);
+        let numbers = tile_map_with_bombs(3, 3, &[at(1, 1)]);
+        assert_eq!(numbers.solved_bbbv(|c| c.y == 0), 3);
+    }
+}
@@ -0,0 +1,237 @@
+use crate::components::Coordinates;
+#[cfg(feature = "plugin")]
+use bevy::prelude::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Shape of the tiles and which tiles are neighbors
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Topology {
+    // Square tiles with 8 neighbors
+    #[default]
+    Square,
+    // Pointy top hexagons with 6 neighbors, odd lines are shifted right by half a tile
+    Hexagonal,
+    // Alternating up and down triangles with the 12 tiles sharing a corner as neighbors.
+    // The tiles with an even 'x + y' point up
+    Triangular,
+}
+
+impl Topology {
+    /// Delta coordinates of the neighbors of the tile at 'coords'
+    pub fn neighbor_deltas(&self, coords: Coordinates) -> &'static [(i8, i8)] {
+        match self {
+            Self::Square => &SQUARE_COORDINATES,
+            Self::Hexagonal if coords.y % 2 == 0 => &HEX_EVEN_LINE_COORDINATES,
+            Self::Hexagonal => &HEX_ODD_LINE_COORDINATES,
+            Self::Triangular if Self::points_up(coords) => &TRIANGLE_UP_COORDINATES,
+            Self::Triangular => &TRIANGLE_DOWN_COORDINATES,
+        }
+    }
+
+    /// Maximum number of neighbors of a tile
+    pub fn max_neighbors(&self) -> usize {
+        match self {
+            Self::Square => SQUARE_COORDINATES.len(),
+            Self::Hexagonal => HEX_EVEN_LINE_COORDINATES.len(),
+            Self::Triangular => TRIANGLE_UP_COORDINATES.len(),
+        }
+    }
+
+    /// Does the triangular tile at 'coords' point up?
+    fn points_up(coords: Coordinates) -> bool {
+        (coords.x + coords.y).rem_euclid(2) == 0
+    }
+}
+
+/// Tile layout in board space, with 'tile_size' the width of a tile
+#[cfg(feature = "plugin")]
+impl Topology {
+    /// Size of a board of 'width' x 'height' tiles
+    pub fn board_size(&self, (width, height): (u16, u16), tile_size: f32) -> Vec2 {
+        let (width, height) = (width as f32, height as f32);
+        match self {
+            Self::Square => Vec2::new(width, height) * tile_size,
+            Self::Hexagonal => {
+                // odd lines stick out by half a tile
+                let shift = if height > 1. { 0.5 } else { 0. };
+                let radius = tile_size / SQRT_3;
+                Vec2::new(
+                    (width + shift) * tile_size,
+                    radius * (1.5 * (height - 1.) + 2.),
+                )
+            }
+            Self::Triangular => Vec2::new(
+                (width + 1.) / 2. * tile_size,
+                height * tile_size * SQRT_3 / 2.,
+            ),
+        }
+    }
+
+    /// Center of the tile at 'coords'
+    pub fn tile_center(&self, coords: Coordinates, tile_size: f32) -> Vec2 {
+        let (x, y) = (coords.x as f32, coords.y as f32);
+        match self {
+            Self::Square => Vec2::new(x + 0.5, y + 0.5) * tile_size,
+            Self::Hexagonal => {
+                let radius = tile_size / SQRT_3;
+                let shift = (coords.y % 2) as f32 / 2.;
+                Vec2::new((x + 0.5 + shift) * tile_size, 1.5 * radius * y + radius)
+            }
+            Self::Triangular => {
+                let line_height = tile_size * SQRT_3 / 2.;
+                // the centroid is a third of the way from the base
+                let offset = if Self::points_up(coords) { 1. } else { 2. } / 3.;
+                Vec2::new((x / 2. + 0.5) * tile_size, (y + offset) * line_height)
+            }
+        }
+    }
+
+    /// Coordinates of the tile containing 'position', if it is on a tile
+    pub fn tile_at_position(&self, position: Vec2, tile_size: f32) -> Option<Coordinates> {
+        if position.x < 0. || position.y < 0. {
+            return None;
+        }
+        match self {
+            Self::Square => to_coordinates(position.x / tile_size, position.y / tile_size),
+            Self::Hexagonal => {
+                // Pixel to axial coordinates, rounded through cube coordinates
+                let radius = tile_size / SQRT_3;
+                let p = position - Vec2::new(tile_size / 2., radius);
+                let q = (SQRT_3 / 3. * p.x - p.y / 3.) / radius;
+                let r = (2. / 3. * p.y) / radius;
+                let s = -q - r;
+                let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+                let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+                if dq > dr && dq > ds {
+                    rq = -rr - rs;
+                } else if dr > ds {
+                    rr = -rq - rs;
+                }
+                // Axial to odd line offset coordinates
+                let line = rr as i64;
+                let column = rq as i64 + (line - (line & 1)) / 2;
+                to_coordinates(column as f32, line as f32)
+            }
+            Self::Triangular => {
+                let line_height = tile_size * SQRT_3 / 2.;
+                let line = (position.y / line_height).floor();
+                // height in the line, from 0 at its bottom to 1 at its top
+                let v = position.y / line_height - line;
+                let u = position.x / (tile_size / 2.);
+                // A tile 'x' spans [x, x + 2] half tiles, narrowing to its tip
+                let first = u.floor() - 1.;
+                (first.max(0.) as i64..=u.floor() as i64).find_map(|x| {
+                    let coords = to_coordinates(x as f32, line)?;
+                    let (left, right) = if Self::points_up(coords) {
+                        (x as f32 + v, x as f32 + 2. - v)
+                    } else {
+                        (x as f32 + 1. - v, x as f32 + 1. + v)
+                    };
+                    (left <= u && u <= right).then_some(coords)
+                })
+            }
+        }
+    }
+
+    /// Side of the largest square sprite fitting in a tile
+    pub fn sprite_size(&self, tile_size: f32) -> f32 {
+        match self {
+            Self::Square => tile_size,
+            // a square inscribed in a hexagon of width 1 has a side of (√3 - 1)
+            Self::Hexagonal => tile_size * (SQRT_3 - 1.),
+            // a square inscribed in a triangle of side 1 has a side of √3 / (2 + √3)
+            Self::Triangular => tile_size * SQRT_3 / (2. + SQRT_3),
+        }
+    }
+}
+
+#[cfg(feature = "plugin")]
+const SQRT_3: f32 = 1.732_050_8;
+
+#[cfg(feature = "plugin")]
+fn to_coordinates(x: f32, y: f32) -> Option<Coordinates> {
+    let (x, y) = (x.floor(), y.floor());
+    if x < 0. || y < 0. || x > i32::MAX as f32 || y > i32::MAX as f32 {
+        return None;
+    }
+    Some(Coordinates {
+        x: x as i32,
+        y: y as i32,
+    })
+}
+
+/// Delta coordinates for all 8 square neightbors
+const SQUARE_COORDINATES: [(i8, i8); 8] = [
+    // Bottom Left
+    (-1, -1),
+    // Bottom
+    (0, -1),
+    // Bottom Right
+    (1, -1),
+    // Left
+    (-1, 0),
+    // Right
+    (1, 0),
+    // Top Left
+    (-1, 1),
+    // Top
+    (0, 1),
+    // Top right
+    (1, 1),
+];
+
+/// Delta coordinates for the 6 hexagon neighbors of a tile on an even line
+const HEX_EVEN_LINE_COORDINATES: [(i8, i8); 6] = [
+    (-1, -1),
+    (0, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+];
+
+/// Delta coordinates for the 6 hexagon neighbors of a tile on an odd line
+const HEX_ODD_LINE_COORDINATES: [(i8, i8); 6] = [
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (0, 1),
+    (1, 1),
+];
+
+/// Delta coordinates for the 12 neighbors of a triangle pointing up
+const TRIANGLE_UP_COORDINATES: [(i8, i8); 12] = [
+    // The whole line below shares the base
+    (-2, -1),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (2, -1),
+    (-2, 0),
+    (-1, 0),
+    (1, 0),
+    (2, 0),
+    // Only the tiles around the tip on the line above
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Delta coordinates for the 12 neighbors of a triangle pointing down
+const TRIANGLE_DOWN_COORDINATES: [(i8, i8); 12] = [
+    // Only the tiles around the tip on the line below
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-2, 0),
+    (-1, 0),
+    (1, 0),
+    (2, 0),
+    // The whole line above shares the base
+    (-2, 1),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (2, 1),
+];
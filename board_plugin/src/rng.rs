@@ -0,0 +1,90 @@
+/// Small deterministic random number generator used for board generation.
+///
+/// Implements xoshiro256** seeded through SplitMix64, with an unbiased range reduction, so a seed
+/// produces the same sequence on every platform and doesn't depend on the `rand` crate version.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: [u64; 4],
+}
+
+impl SeededRng {
+    /// Creates a generator from a 64 bits seed
+    pub fn new(seed: u64) -> Self {
+        let mut splitmix = seed;
+        let mut state = [0; 4];
+        for s in state.iter_mut() {
+            *s = split_mix64(&mut splitmix);
+        }
+        Self { state }
+    }
+
+    /// Next random 64 bits value
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1]
+            .wrapping_mul(5)
+            .rotate_left(7)
+            .wrapping_mul(9);
+        let t = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    /// Random value in `0..bound`, `bound` must not be 0
+    pub fn gen_below(&mut self, bound: u64) -> u64 {
+        // Lemire's multiply and reject method, avoids the modulo bias
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let product = self.next_u64() as u128 * bound as u128;
+            if (product as u64) >= threshold {
+                return (product >> 64) as u64;
+            }
+        }
+    }
+}
+
+fn split_mix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::tile_map::TileMap;
+
+    #[test]
+    fn a_seed_gives_a_fixed_sequence() {
+        let mut rng = SeededRng::new(42);
+        let values: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+        assert_eq!(
+            values,
+            [
+                0x1578_0B2E_0C2E_C716,
+                0x6104_D986_6D11_3A7E,
+                0xAE17_5332_39E4_99A1,
+                0xECB8_AD47_03B3_60A1,
+            ]
+        );
+        let mut rng = SeededRng::new(42);
+        let values: Vec<u64> = (0..10).map(|_| rng.gen_below(10)).collect();
+        assert_eq!(values, [0, 3, 6, 9, 9, 7, 7, 8, 7, 5]);
+    }
+
+    #[test]
+    fn a_seed_gives_the_same_map() {
+        let mut first = TileMap::empty(16, 16);
+        first.set_bombs(40, 7).unwrap();
+        let mut second = TileMap::empty(16, 16);
+        second.set_bombs(40, 7).unwrap();
+        assert_eq!(*first, *second);
+        assert_eq!(first.bomb_count(), second.bomb_count());
+    }
+}
@@ -0,0 +1,252 @@
+use crate::rng::SeededRng;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Bombs a revealed number still needs among its unknown neighbors
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Constraint {
+    // index of the revealed number
+    pub origin: usize,
+    // sorted indexes of the unknown neighbors
+    pub cells: Vec<usize>,
+    // bombs left among 'cells'
+    pub bombs: usize,
+}
+
+/// Bomb arrangements of a component sharing the same bomb count
+#[derive(Debug, Clone)]
+pub(crate) struct Arrangements {
+    // number of arrangements
+    pub count: u64,
+    // for each component cell, number of arrangements with a bomb on it
+    pub bomb_tallies: Vec<u64>,
+}
+
+/// Group of unknown cells linked by constraints, independent from the other groups
+#[derive(Debug, Clone)]
+pub(crate) struct Component {
+    // sorted cell indexes
+    pub cells: Vec<usize>,
+    // indexes in the constraint list
+    pub constraints: Vec<usize>,
+    // arrangements by bomb count, `None` if the component was too large to enumerate
+    pub arrangements: Option<BTreeMap<usize, Arrangements>>,
+}
+
+impl Component {
+    /// Bomb counts the component can hold, ignoring the global bomb count
+    pub fn bomb_counts(&self) -> Vec<usize> {
+        match &self.arrangements {
+            Some(arrangements) => arrangements.keys().copied().collect(),
+            None => (0..=self.cells.len()).collect(),
+        }
+    }
+}
+
+/// Splits the constraints into independent components and enumerates them within the budget
+pub(crate) fn components(
+    constraints: &[Constraint],
+    max_cells: usize,
+    max_steps: usize,
+) -> Vec<Component> {
+    // Union find over the constraints, two constraints sharing a cell belong together
+    let mut parents: Vec<usize> = (0..constraints.len()).collect();
+    fn find(parents: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parents[root] != root {
+            root = parents[root];
+        }
+        parents[i] = root;
+        root
+    }
+    let mut owner: BTreeMap<usize, usize> = BTreeMap::new();
+    for (i, constraint) in constraints.iter().enumerate() {
+        for cell in constraint.cells.iter() {
+            match owner.get(cell) {
+                None => {
+                    owner.insert(*cell, i);
+                }
+                Some(j) => {
+                    let (a, b) = (find(&mut parents, i), find(&mut parents, *j));
+                    parents[a] = b;
+                }
+            }
+        }
+    }
+    let mut groups: BTreeMap<usize, Component> = BTreeMap::new();
+    for (i, constraint) in constraints.iter().enumerate() {
+        let root = find(&mut parents, i);
+        let component = groups.entry(root).or_insert_with(|| Component {
+            cells: vec![],
+            constraints: vec![],
+            arrangements: None,
+        });
+        component.constraints.push(i);
+        component.cells.extend(constraint.cells.iter().copied());
+    }
+    groups
+        .into_values()
+        .map(|mut component| {
+            component.cells.sort_unstable();
+            component.cells.dedup();
+            if component.cells.len() <= max_cells {
+                component.arrangements = enumerate(&component, constraints, max_steps);
+            }
+            component
+        })
+        .collect()
+}
+
+/// Enumerates every bomb arrangement of a component, `None` if the step budget runs out
+fn enumerate(
+    component: &Component,
+    constraints: &[Constraint],
+    max_steps: usize,
+) -> Option<BTreeMap<usize, Arrangements>> {
+    let mut search = Search::new(component, constraints, max_steps, None);
+    if search.run(0, 0) {
+        Some(search.result)
+    } else {
+        None
+    }
+}
+
+/// Collects up to 'samples' random bomb arrangements of a component too large to be enumerated.
+///
+/// Each sample is the first arrangement found by a search taking random branches. The searches
+/// favor some arrangements, so each arrangement is only counted once however often it is found:
+/// the result is exact when every arrangement is found, an approximation otherwise.
+pub(crate) fn sample(
+    component: &Component,
+    constraints: &[Constraint],
+    samples: usize,
+    max_steps: usize,
+    seed: u64,
+) -> BTreeMap<usize, Arrangements> {
+    let mut search = Search::new(component, constraints, max_steps, Some(SeededRng::new(seed)));
+    for _ in 0..samples {
+        search.steps = 0;
+        search.run(0, 0);
+    }
+    search.result
+}
+
+/// Backtracking state of an enumeration
+struct Search {
+    order: Vec<usize>,
+    cell_constraints: Vec<Vec<usize>>,
+    bombs: Vec<usize>,
+    placed: Vec<usize>,
+    unassigned: Vec<usize>,
+    assignment: Vec<bool>,
+    steps: usize,
+    max_steps: usize,
+    // Random branch order, the search then stops on the first arrangement found
+    rng: Option<SeededRng>,
+    // arrangements already counted by a random search
+    found: BTreeSet<Vec<bool>>,
+    result: BTreeMap<usize, Arrangements>,
+}
+
+impl Search {
+    fn new(
+        component: &Component,
+        constraints: &[Constraint],
+        max_steps: usize,
+        rng: Option<SeededRng>,
+    ) -> Self {
+        let local = |cell: &usize| component.cells.binary_search(cell).unwrap();
+        let local_constraints: Vec<(Vec<usize>, usize)> = component
+            .constraints
+            .iter()
+            .map(|i| (constraints[*i].cells.iter().map(local).collect(), constraints[*i].bombs))
+            .collect();
+        let mut cell_constraints = vec![vec![]; component.cells.len()];
+        for (c, (cells, _)) in local_constraints.iter().enumerate() {
+            for cell in cells {
+                cell_constraints[*cell].push(c);
+            }
+        }
+        // Cells are assigned constraint by constraint so contradictions are found early
+        let mut order = Vec::with_capacity(component.cells.len());
+        let mut ordered = vec![false; component.cells.len()];
+        for (cells, _) in local_constraints.iter() {
+            for cell in cells {
+                if !ordered[*cell] {
+                    ordered[*cell] = true;
+                    order.push(*cell);
+                }
+            }
+        }
+        Self {
+            order,
+            cell_constraints,
+            bombs: local_constraints.iter().map(|(_, b)| *b).collect(),
+            placed: vec![0; local_constraints.len()],
+            unassigned: local_constraints.iter().map(|(c, _)| c.len()).collect(),
+            assignment: vec![false; component.cells.len()],
+            steps: 0,
+            max_steps,
+            rng,
+            found: BTreeSet::new(),
+            result: BTreeMap::new(),
+        }
+    }
+
+    /// Explores the assignments from the 'depth' cell of the order, false if the search must
+    /// stop because the budget ran out or a sample was found
+    fn run(&mut self, depth: usize, bomb_count: usize) -> bool {
+        self.steps += 1;
+        if self.steps > self.max_steps {
+            return false;
+        }
+        if depth == self.order.len() {
+            if self.rng.is_some() && !self.found.insert(self.assignment.clone()) {
+                return false;
+            }
+            let cells = self.assignment.len();
+            let arrangements = self.result.entry(bomb_count).or_insert_with(|| Arrangements {
+                count: 0,
+                bomb_tallies: vec![0; cells],
+            });
+            arrangements.count += 1;
+            for (tally, bomb) in arrangements.bomb_tallies.iter_mut().zip(self.assignment.iter()) {
+                if *bomb {
+                    *tally += 1;
+                }
+            }
+            return self.rng.is_none();
+        }
+        let cell = self.order[depth];
+        let flip = self.rng.as_mut().map_or(false, |rng| rng.next_u64() & 1 == 1);
+        let branches = if flip { [true, false] } else { [false, true] };
+        for bomb in branches {
+            let valid = self.assign(cell, bomb);
+            if valid && !self.run(depth + 1, bomb_count + bomb as usize) {
+                self.unassign(cell, bomb);
+                return false;
+            }
+            self.unassign(cell, bomb);
+        }
+        true
+    }
+    /// Assigns a cell, returns whether its constraints can still be met
+    fn assign(&mut self, cell: usize, bomb: bool) -> bool {
+        self.assignment[cell] = bomb;
+        let mut valid = true;
+        for c in self.cell_constraints[cell].iter() {
+            self.unassigned[*c] -= 1;
+            self.placed[*c] += bomb as usize;
+            valid &= self.placed[*c] <= self.bombs[*c]
+                && self.placed[*c] + self.unassigned[*c] >= self.bombs[*c];
+        }
+        valid
+    }
+
+    fn unassign(&mut self, cell: usize, bomb: bool) {
+        self.assignment[cell] = false;
+        for c in self.cell_constraints[cell].iter() {
+            self.unassigned[*c] += 1;
+            self.placed[*c] -= bomb as usize;
+        }
+    }
+}
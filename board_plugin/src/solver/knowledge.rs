@@ -0,0 +1,195 @@
+use crate::components::Coordinates;
+use crate::game::Game;
+#[cfg(feature = "plugin")]
+use crate::resources::board::Board;
+use crate::resources::tile::Tile;
+use crate::resources::tile_map::TileMap;
+
+/// What the player knows about a tile
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CellKnowledge {
+    // Covered tile
+    Covered,
+    // Covered tile flagged as a bomb, or an exploded bomb
+    Flagged,
+    // Uncovered tile with its number of neighbor bombs
+    Revealed(u8),
+    // Hole of a masked board, never a bomb
+    Outside,
+}
+
+/// Player knowledge of a board: the revealed numbers and the flags, never the hidden bombs
+#[derive(Debug, Clone)]
+pub struct BoardKnowledge {
+    width: u16,
+    height: u16,
+    bomb_count: u16,
+    cells: Vec<CellKnowledge>,
+    // neighbor indexes of every cell, holes of masked boards excluded
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl BoardKnowledge {
+    /// Knowledge of a fully covered board, only the geometry and bomb count of 'tile_map' are used
+    pub fn covered(tile_map: &TileMap, bomb_count: u16) -> Self {
+        let (width, height) = (tile_map.width(), tile_map.height());
+        let all_coordinates = (0..height as i32)
+            .flat_map(|y| (0..width as i32).map(move |x| Coordinates { x, y }));
+        let neighbors = all_coordinates
+            .clone()
+            .map(|coords| {
+                tile_map
+                    .safe_square_at(coords)
+                    .map(|c| c.y as usize * width as usize + c.x as usize)
+                    .collect()
+            })
+            .collect();
+        let cells = all_coordinates
+            .map(|coords| {
+                if tile_map.contains(coords) {
+                    CellKnowledge::Covered
+                } else {
+                    CellKnowledge::Outside
+                }
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            bomb_count,
+            cells,
+            neighbors,
+        }
+    }
+
+    /// Knowledge the player has of a game
+    pub fn from_game(game: &Game) -> Self {
+        let mut knowledge = Self::covered(game.tile_map(), game.bomb_count());
+        for index in 0..knowledge.cells.len() {
+            let coords = knowledge.coordinates(index);
+            if knowledge.cells[index] == CellKnowledge::Outside {
+                continue;
+            }
+            knowledge.cells[index] = if !game.is_revealed(coords) {
+                if game.is_flagged(coords) {
+                    CellKnowledge::Flagged
+                } else {
+                    CellKnowledge::Covered
+                }
+            } else {
+                match game.tile_map().tile_at(coords) {
+                    Some(Tile::BombNeighbor(v)) => CellKnowledge::Revealed(revealed_count(*v)),
+                    Some(tile) if tile.is_bomb() => CellKnowledge::Flagged,
+                    _ => CellKnowledge::Revealed(0),
+                }
+            };
+        }
+        knowledge
+    }
+
+    /// Knowledge the player has of the current board
+    #[cfg(feature = "plugin")]
+    pub fn from_board(board: &Board) -> Self {
+        Self::from_game(&board.game)
+    }
+
+    /// Getter for 'width'
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Getter for 'height'
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Getter for 'bomb_count'
+    pub fn bomb_count(&self) -> u16 {
+        self.bomb_count
+    }
+
+    /// Retrieves the knowledge of a tile, if in bounds
+    pub fn get(&self, coords: Coordinates) -> Option<CellKnowledge> {
+        self.index(coords).map(|i| self.cells[i])
+    }
+
+    /// Updates the knowledge of a tile, out of bounds coordinates are ignored
+    pub fn set(&mut self, coords: Coordinates, cell: CellKnowledge) {
+        if let Some(i) = self.index(coords) {
+            self.cells[i] = cell;
+        }
+    }
+
+    /// Uncovers a tile with the number of 'tile_map', opening the empty area it belongs to
+    pub fn reveal(&mut self, tile_map: &TileMap, coords: Coordinates) {
+        let mut stack = vec![coords];
+        while let Some(coords) = stack.pop() {
+            if self.get(coords) != Some(CellKnowledge::Covered) {
+                continue;
+            }
+            match tile_map.tile_at(coords) {
+                Some(Tile::BombNeighbor(v)) => {
+                    self.set(coords, CellKnowledge::Revealed(revealed_count(*v)))
+                }
+                Some(Tile::Empty) => {
+                    self.set(coords, CellKnowledge::Revealed(0));
+                    stack.extend(self.neighbors(coords));
+                }
+                _ => self.set(coords, CellKnowledge::Flagged),
+            }
+        }
+    }
+
+    /// Number of tiles still covered and not flagged
+    pub fn covered_count(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|c| **c == CellKnowledge::Covered)
+            .count()
+    }
+
+    /// Neighbors of a tile which are part of the board
+    pub fn neighbors(&self, coords: Coordinates) -> impl Iterator<Item = Coordinates> + '_ {
+        self.index(coords)
+            .map(|i| self.neighbors[i].as_slice())
+            .unwrap_or(&[])
+            .iter()
+            .map(move |i| self.coordinates(*i))
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub(crate) fn cell(&self, index: usize) -> CellKnowledge {
+        self.cells[index]
+    }
+
+    pub(crate) fn neighbor_indexes(&self, index: usize) -> &[usize] {
+        &self.neighbors[index]
+    }
+
+    pub(crate) fn index(&self, coords: Coordinates) -> Option<usize> {
+        if coords.x < 0
+            || coords.y < 0
+            || coords.x >= self.width as i32
+            || coords.y >= self.height as i32
+        {
+            return None;
+        }
+        Some(coords.y as usize * self.width as usize + coords.x as usize)
+    }
+
+    pub(crate) fn coordinates(&self, index: usize) -> Coordinates {
+        Coordinates {
+            x: (index % self.width as usize) as i32,
+            y: (index / self.width as usize) as i32,
+        }
+    }
+}
+
+/// Number of a revealed tile as the solver sees it. The solver only runs with the classic rules,
+/// where the numbers are positive
+fn revealed_count(count: i16) -> u8 {
+    count.clamp(0, u8::MAX as i16) as u8
+}
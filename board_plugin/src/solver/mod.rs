@@ -0,0 +1,575 @@
+//! Logic solver reasoning on the player knowledge of a board.
+//!
+//! Deductions only use the revealed numbers, the flags and the total bomb count, so they are
+//! exactly what a player could figure out without guessing.
+mod enumeration;
+mod knowledge;
+mod no_guess;
+mod probability;
+
+pub use knowledge::{BoardKnowledge, CellKnowledge};
+pub use no_guess::{generate_no_guess, NoGuessReport};
+pub use probability::BombProbabilities;
+
+use crate::components::Coordinates;
+use crate::resources::tile_map::TileMap;
+use enumeration::{Component, Constraint};
+
+/// Why a tile is known to be safe or a bomb
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Reason {
+    // The number already has all its bombs around, its other covered neighbors are safe
+    Satisfied {
+        number: Coordinates,
+        count: u8,
+        bombs: Vec<Coordinates>,
+    },
+    // The number needs all its covered neighbors to be bombs
+    Saturated { number: Coordinates, count: u8 },
+    // The unknown neighbors of 'subset' are all neighbors of 'superset', the other unknown
+    // neighbors of 'superset' hold the bomb count difference
+    Subset {
+        subset: Coordinates,
+        superset: Coordinates,
+    },
+    // Every bomb arrangement matching the numbers agrees on the tile
+    Enumeration,
+    // Every bomb arrangement matching the numbers and the total bomb count agrees on the tile
+    BombCount,
+}
+
+/// A tile whose content is certain
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Deduction {
+    pub coordinates: Coordinates,
+    pub is_bomb: bool,
+    pub reason: Reason,
+}
+
+impl Deduction {
+    /// Human readable explanation of the deduction
+    pub fn explanation(&self, knowledge: &BoardKnowledge) -> String {
+        let number = |coords: &Coordinates| match knowledge.get(*coords) {
+            Some(CellKnowledge::Revealed(v)) => v,
+            _ => 0,
+        };
+        let content = if self.is_bomb { "a bomb" } else { "safe" };
+        let because = match &self.reason {
+            Reason::Satisfied {
+                number,
+                count,
+                bombs,
+            } => {
+                if bombs.is_empty() {
+                    format!("the {} at {} has no bomb around", count, number)
+                } else {
+                    let bombs: Vec<String> = bombs
+                        .iter()
+                        .map(|b| match knowledge.get(*b) {
+                            Some(CellKnowledge::Flagged) => format!("the flag at {}", b),
+                            _ => format!("the bomb at {}", b),
+                        })
+                        .collect();
+                    format!(
+                        "the {} at {} is already satisfied by {}",
+                        count,
+                        number,
+                        bombs.join(" and ")
+                    )
+                }
+            }
+            Reason::Saturated { number, count } => format!(
+                "the {} at {} needs all its covered neighbors to be bombs",
+                count, number
+            ),
+            Reason::Subset { subset, superset } => format!(
+                "the {} at {} shares all its covered neighbors with the {} at {}, \
+                 leaving {} for the other ones",
+                number(subset),
+                subset,
+                number(superset),
+                superset,
+                if self.is_bomb { "only bombs" } else { "no bomb" }
+            ),
+            Reason::Enumeration => {
+                "every bomb arrangement matching the numbers agrees".to_string()
+            }
+            Reason::BombCount => {
+                "every bomb arrangement matching the numbers and the bomb count agrees".to_string()
+            }
+        };
+        format!("{} is {} because {}", self.coordinates, content, because)
+    }
+}
+
+/// Logic solver options
+#[derive(Debug, Clone)]
+pub struct Solver {
+    // Largest group of linked frontier tiles to enumerate
+    pub max_enumeration_cells: usize,
+    // Backtracking steps allowed for a single group
+    pub max_enumeration_steps: usize,
+    // Random arrangements drawn to estimate the bomb probabilities of the groups too large to
+    // be enumerated
+    pub probability_samples: usize,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self {
+            max_enumeration_cells: 48,
+            max_enumeration_steps: 200_000,
+            probability_samples: 500,
+        }
+    }
+}
+
+impl Solver {
+    /// Deduces every certainly safe and certainly mined covered tile, simplest rules first.
+    ///
+    /// Deduced bombs are used to go further but deduced safe tiles are not uncovered, so the
+    /// caller should reveal them and call the solver again.
+    pub fn deduce(&self, knowledge: &BoardKnowledge) -> Vec<Deduction> {
+        let mut state = SolverState::new(knowledge);
+        let mut deductions = vec![];
+        loop {
+            let constraints = state.constraints();
+            let mut found = state.single_tile_rules(&constraints);
+            if found.is_empty() {
+                found = state.subset_rules(&constraints);
+            }
+            if found.is_empty() {
+                found = state.enumeration_rules(&constraints, self);
+            }
+            let mut progress = false;
+            for deduction in found {
+                let index = knowledge.index(deduction.coordinates).unwrap();
+                if state.known[index].is_none() {
+                    state.known[index] = Some(deduction.is_bomb);
+                    deductions.push(deduction);
+                    progress = true;
+                }
+            }
+            if !progress {
+                return deductions;
+            }
+        }
+    }
+
+    /// Can the whole board be cleared without guessing once 'start' is uncovered?
+    pub fn can_clear(&self, tile_map: &TileMap, start: Coordinates) -> bool {
+        if tile_map.is_bomb_at(start) {
+            return false;
+        }
+        let mut knowledge = BoardKnowledge::covered(tile_map, tile_map.bomb_count());
+        knowledge.reveal(tile_map, start);
+        loop {
+            let covered = knowledge.covered_count();
+            if covered == 0 {
+                return true;
+            }
+            for deduction in self.deduce(&knowledge) {
+                if deduction.is_bomb {
+                    knowledge.set(deduction.coordinates, CellKnowledge::Flagged);
+                } else {
+                    knowledge.reveal(tile_map, deduction.coordinates);
+                }
+            }
+            if knowledge.covered_count() == covered {
+                return false;
+            }
+        }
+    }
+}
+
+/// Knowledge augmented with the solver deductions
+struct SolverState<'a> {
+    knowledge: &'a BoardKnowledge,
+    // `Some(true)` for known bombs, `Some(false)` for known safe tiles
+    known: Vec<Option<bool>>,
+}
+
+impl<'a> SolverState<'a> {
+    fn new(knowledge: &'a BoardKnowledge) -> Self {
+        let known = (0..knowledge.len())
+            .map(|i| match knowledge.cell(i) {
+                CellKnowledge::Covered => None,
+                CellKnowledge::Flagged => Some(true),
+                CellKnowledge::Revealed(_) | CellKnowledge::Outside => Some(false),
+            })
+            .collect();
+        Self { knowledge, known }
+    }
+
+    /// Bombs left to find among the unknown tiles, `None` if more bombs are known than exist
+    fn remaining_bombs(&self) -> Option<usize> {
+        let known_bombs = self.known.iter().filter(|k| **k == Some(true)).count();
+        (self.knowledge.bomb_count() as usize).checked_sub(known_bombs)
+    }
+
+    /// Constraints of the revealed numbers with unknown neighbors. Inconsistent numbers, which
+    /// can only come from misplaced flags, are ignored
+    fn constraints(&self) -> Vec<Constraint> {
+        let mut constraints: Vec<Constraint> = vec![];
+        for origin in 0..self.knowledge.len() {
+            let count = match self.knowledge.cell(origin) {
+                CellKnowledge::Revealed(v) => v as usize,
+                _ => continue,
+            };
+            let neighbors = self.knowledge.neighbor_indexes(origin);
+            let mut cells: Vec<usize> = neighbors
+                .iter()
+                .copied()
+                .filter(|n| self.known[*n].is_none())
+                .collect();
+            if cells.is_empty() {
+                continue;
+            }
+            cells.sort_unstable();
+            let known_bombs = neighbors
+                .iter()
+                .filter(|n| self.known[**n] == Some(true))
+                .count();
+            let bombs = match count.checked_sub(known_bombs) {
+                Some(b) if b <= cells.len() => b,
+                _ => continue,
+            };
+            if constraints.iter().any(|c| c.cells == cells) {
+                continue;
+            }
+            constraints.push(Constraint {
+                origin,
+                cells,
+                bombs,
+            });
+        }
+        constraints
+    }
+
+    /// Number of unknown tiles not constrained by any number
+    fn interior_count(&self, constraints: &[Constraint]) -> usize {
+        let mut frontier = vec![false; self.known.len()];
+        for constraint in constraints {
+            for cell in constraint.cells.iter() {
+                frontier[*cell] = true;
+            }
+        }
+        (0..self.known.len())
+            .filter(|i| self.known[*i].is_none() && !frontier[*i])
+            .count()
+    }
+
+    fn deduction(&self, index: usize, is_bomb: bool, reason: Reason) -> Deduction {
+        Deduction {
+            coordinates: self.knowledge.coordinates(index),
+            is_bomb,
+            reason,
+        }
+    }
+
+    /// A number with no bomb left, or with as many bombs left as unknown neighbors
+    fn single_tile_rules(&self, constraints: &[Constraint]) -> Vec<Deduction> {
+        let mut deductions = vec![];
+        for constraint in constraints {
+            let number = self.knowledge.coordinates(constraint.origin);
+            let count = match self.knowledge.cell(constraint.origin) {
+                CellKnowledge::Revealed(v) => v,
+                _ => continue,
+            };
+            if constraint.bombs == 0 {
+                let bombs: Vec<Coordinates> = self
+                    .knowledge
+                    .neighbor_indexes(constraint.origin)
+                    .iter()
+                    .filter(|n| self.known[**n] == Some(true))
+                    .map(|n| self.knowledge.coordinates(*n))
+                    .collect();
+                for cell in constraint.cells.iter() {
+                    let reason = Reason::Satisfied {
+                        number,
+                        count,
+                        bombs: bombs.clone(),
+                    };
+                    deductions.push(self.deduction(*cell, false, reason));
+                }
+            } else if constraint.bombs == constraint.cells.len() {
+                for cell in constraint.cells.iter() {
+                    let reason = Reason::Saturated { number, count };
+                    deductions.push(self.deduction(*cell, true, reason));
+                }
+            }
+        }
+        deductions
+    }
+
+    /// A number whose unknown neighbors are all shared with another number
+    fn subset_rules(&self, constraints: &[Constraint]) -> Vec<Deduction> {
+        let mut deductions = vec![];
+        for subset in constraints {
+            for superset in constraints {
+                if subset.cells.len() >= superset.cells.len()
+                    || superset.bombs < subset.bombs
+                    || !subset
+                        .cells
+                        .iter()
+                        .all(|c| superset.cells.binary_search(c).is_ok())
+                {
+                    continue;
+                }
+                let difference: Vec<usize> = superset
+                    .cells
+                    .iter()
+                    .copied()
+                    .filter(|c| subset.cells.binary_search(c).is_err())
+                    .collect();
+                let bombs = superset.bombs - subset.bombs;
+                let is_bomb = match bombs {
+                    0 => false,
+                    b if b == difference.len() => true,
+                    _ => continue,
+                };
+                for cell in difference {
+                    let reason = Reason::Subset {
+                        subset: self.knowledge.coordinates(subset.origin),
+                        superset: self.knowledge.coordinates(superset.origin),
+                    };
+                    deductions.push(self.deduction(cell, is_bomb, reason));
+                }
+            }
+        }
+        deductions
+    }
+
+    /// Enumerates the bomb arrangements of the frontier, then combines them with the total bomb
+    /// count to also decide the tiles away from the frontier
+    fn enumeration_rules(&self, constraints: &[Constraint], solver: &Solver) -> Vec<Deduction> {
+        let components = enumeration::components(
+            constraints,
+            solver.max_enumeration_cells,
+            solver.max_enumeration_steps,
+        );
+        let mut deductions = vec![];
+        // Local arrangements first
+        for component in components.iter() {
+            if let Some(arrangements) = &component.arrangements {
+                let counts: Vec<usize> = arrangements.keys().copied().collect();
+                for (cell, is_bomb) in certain_cells(component, &counts) {
+                    deductions.push(self.deduction(cell, is_bomb, Reason::Enumeration));
+                }
+            }
+        }
+        if !deductions.is_empty() {
+            return deductions;
+        }
+
+        // Then restricted to the arrangements matching the remaining bomb count
+        let remaining = match self.remaining_bombs() {
+            Some(r) => r,
+            None => return deductions,
+        };
+        let interior = self.interior_count(constraints);
+        let bomb_counts: Vec<Vec<usize>> = components.iter().map(|c| c.bomb_counts()).collect();
+        for (i, component) in components.iter().enumerate() {
+            if component.arrangements.is_none() {
+                continue;
+            }
+            let others = reachable_sums(&bomb_counts, Some(i), remaining);
+            let feasible: Vec<usize> = bomb_counts[i]
+                .iter()
+                .copied()
+                .filter(|k| fits(&others, remaining, *k, interior))
+                .collect();
+            for (cell, is_bomb) in certain_cells(component, &feasible) {
+                deductions.push(self.deduction(cell, is_bomb, Reason::BombCount));
+            }
+        }
+        // The tiles away from the frontier are all safe or all bombs
+        if interior > 0 {
+            let sums = reachable_sums(&bomb_counts, None, remaining);
+            let interior_bombs: Vec<usize> = (0..=remaining)
+                .filter(|s| sums[*s] && remaining - s <= interior)
+                .map(|s| remaining - s)
+                .collect();
+            let is_bomb = if !interior_bombs.is_empty() && interior_bombs.iter().all(|b| *b == 0) {
+                Some(false)
+            } else if !interior_bombs.is_empty() && interior_bombs.iter().all(|b| *b == interior) {
+                Some(true)
+            } else {
+                None
+            };
+            if let Some(is_bomb) = is_bomb {
+                let mut frontier = vec![false; self.known.len()];
+                for cell in components.iter().flat_map(|c| c.cells.iter()) {
+                    frontier[*cell] = true;
+                }
+                for (cell, known) in self.known.iter().enumerate() {
+                    if known.is_none() && !frontier[cell] {
+                        deductions.push(self.deduction(cell, is_bomb, Reason::BombCount));
+                    }
+                }
+            }
+        }
+        deductions
+    }
+}
+
+/// Cells of an enumerated component with the same content in every arrangement of the 'counts'
+fn certain_cells(component: &Component, counts: &[usize]) -> Vec<(usize, bool)> {
+    let arrangements = match &component.arrangements {
+        Some(a) => a,
+        None => return vec![],
+    };
+    let (total, tallies) = counts
+        .iter()
+        .filter_map(|k| arrangements.get(k))
+        .fold((0, vec![0; component.cells.len()]), |(total, mut tallies), a| {
+            for (tally, t) in tallies.iter_mut().zip(a.bomb_tallies.iter()) {
+                *tally += t;
+            }
+            (total + a.count, tallies)
+        });
+    if total == 0 {
+        return vec![];
+    }
+    component
+        .cells
+        .iter()
+        .zip(tallies.iter())
+        .filter_map(|(cell, tally)| match *tally {
+            0 => Some((*cell, false)),
+            t if t == total => Some((*cell, true)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Total bomb counts up to 'max' reachable by the components, optionally skipping one
+pub(crate) fn reachable_sums(bomb_counts: &[Vec<usize>], skip: Option<usize>, max: usize) -> Vec<bool> {
+    let mut sums = vec![false; max + 1];
+    sums[0] = true;
+    for (i, counts) in bomb_counts.iter().enumerate() {
+        if Some(i) == skip {
+            continue;
+        }
+        let mut next = vec![false; max + 1];
+        for (s, _) in sums.iter().enumerate().filter(|(_, reachable)| **reachable) {
+            for k in counts.iter().filter(|k| s + *k <= max) {
+                next[s + k] = true;
+            }
+        }
+        sums = next;
+    }
+    sums
+}
+
+/// Can a component hold 'k' bombs given the sums reachable by the others and the interior size
+fn fits(others: &[bool], remaining: usize, k: usize, interior: usize) -> bool {
+    others
+        .iter()
+        .enumerate()
+        .any(|(s, reachable)| *reachable && s + k <= remaining && remaining - s - k <= interior)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::tile_map::TileMap;
+    use crate::testing::{opened_knowledge, seeded_tile_map, tile_map_with_bombs};
+
+    /// Player knowledge written row by row: '#' covered, 'F' flagged, digits revealed
+    fn knowledge(rows: &[&str], bomb_count: u16) -> BoardKnowledge {
+        let tile_map = TileMap::empty(rows[0].len() as u16, rows.len() as u16);
+        let mut knowledge = BoardKnowledge::covered(&tile_map, bomb_count);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let cell = match c {
+                    '#' => CellKnowledge::Covered,
+                    'F' => CellKnowledge::Flagged,
+                    _ => CellKnowledge::Revealed(c.to_digit(10).unwrap() as u8),
+                };
+                knowledge.set(at(x as i32, y as i32), cell);
+            }
+        }
+        knowledge
+    }
+
+    fn at(x: i32, y: i32) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    #[test]
+    fn satisfied_number_clears_its_other_neighbors() {
+        let deductions = Solver::default().deduce(&knowledge(&["F1##"], 1));
+        assert_eq!(
+            deductions[0],
+            Deduction {
+                coordinates: at(2, 0),
+                is_bomb: false,
+                reason: Reason::Satisfied {
+                    number: at(1, 0),
+                    count: 1,
+                    bombs: vec![at(0, 0)],
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn saturated_number_makes_its_neighbors_bombs() {
+        let deductions = Solver::default().deduce(&knowledge(&["#10"], 1));
+        assert_eq!(
+            deductions,
+            vec![Deduction {
+                coordinates: at(0, 0),
+                is_bomb: true,
+                reason: Reason::Saturated {
+                    number: at(1, 0),
+                    count: 1,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn subset_of_neighbors_clears_the_difference() {
+        let deductions = Solver::default().deduce(&knowledge(&["01#", "01#", "01#"], 1));
+        for safe in [at(2, 0), at(2, 2)] {
+            assert!(deductions.iter().any(|d| {
+                d.coordinates == safe && !d.is_bomb && matches!(d.reason, Reason::Subset { .. })
+            }));
+        }
+        assert!(deductions.iter().all(|d| d.is_bomb == (d.coordinates == at(2, 1))));
+    }
+
+    #[test]
+    fn bomb_count_decides_the_tiles_away_from_the_numbers() {
+        let deductions = Solver::default().deduce(&knowledge(&["##"], 2));
+        assert_eq!(deductions.len(), 2);
+        assert!(deductions.iter().all(|d| d.is_bomb && d.reason == Reason::BombCount));
+        let deductions = Solver::default().deduce(&knowledge(&["##"], 0));
+        assert_eq!(deductions.len(), 2);
+        assert!(deductions.iter().all(|d| !d.is_bomb && d.reason == Reason::BombCount));
+    }
+
+    #[test]
+    fn deductions_match_the_board() {
+        for seed in 0..20 {
+            let tile_map = seeded_tile_map(seed);
+            for deduction in Solver::default().deduce(&opened_knowledge(&tile_map)) {
+                let is_bomb = tile_map.is_bomb_at(deduction.coordinates);
+                assert_eq!(deduction.is_bomb, is_bomb, "seed {}: {:?}", seed, deduction);
+            }
+        }
+    }
+
+    #[test]
+    fn can_clear_needs_a_way_without_guesses() {
+        let solver = Solver::default();
+        let tile_map = tile_map_with_bombs(5, 3, &[at(0, 0), at(4, 0)]);
+        assert!(solver.can_clear(&tile_map, at(2, 2)));
+        assert!(!solver.can_clear(&tile_map, at(0, 0)));
+        // The 1 can't tell which of its three neighbors is the bomb
+        let tile_map = tile_map_with_bombs(2, 2, &[at(1, 0)]);
+        assert!(!solver.can_clear(&tile_map, at(0, 0)));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::components::Coordinates;
+use crate::resources::tile_map::TileMap;
+use crate::resources::{BoardOptionsError, NoGuessOptions};
+use crate::rng::SeededRng;
+use crate::solver::Solver;
+use std::time::{Duration, Instant};
+
+/// Outcome of a no guess generation
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct NoGuessReport {
+    // Number of generated layouts
+    pub attempts: u32,
+    // Was a layout solvable without guessing found? If not the last layout is used
+    pub solved: bool,
+}
+
+/// Places the bombs of 'tile_map' so that it can be cleared without guessing from 'start',
+/// generating new layouts until one is found or the budget is spent.
+///
+/// The layouts only depend on 'seed', but a time budget can stop the search at different
+/// attempts on different machines.
+pub fn generate_no_guess(
+    tile_map: &mut TileMap,
+    bomb_count: u16,
+    start: Coordinates,
+    excluded: &[Coordinates],
+    seed: u64,
+    options: &NoGuessOptions,
+) -> Result<NoGuessReport, BoardOptionsError> {
+    let solver = Solver::default();
+    let started = Instant::now();
+    let budget = Duration::from_millis(options.time_budget_ms);
+    let empty_map = tile_map.clone();
+    // The first layout is the regular one for this seed
+    let mut seeds = SeededRng::new(seed);
+    let mut attempt_seed = seed;
+    let mut attempts = 0;
+    loop {
+        *tile_map = empty_map.clone();
+        tile_map.set_bombs_excluding(bomb_count, excluded, attempt_seed)?;
+        attempts += 1;
+        if solver.can_clear(tile_map, start) {
+            return Ok(NoGuessReport {
+                attempts,
+                solved: true,
+            });
+        }
+        if attempts >= options.max_attempts.max(1) || started.elapsed() >= budget {
+            return Ok(NoGuessReport {
+                attempts,
+                solved: false,
+            });
+        }
+        attempt_seed = seeds.next_u64();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The time budget would make the outcome depend on the machine
+    fn options() -> NoGuessOptions {
+        NoGuessOptions {
+            time_budget_ms: u64::MAX,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn generated_layouts_can_be_cleared_from_the_start() {
+        for seed in 0..5 {
+            let mut tile_map = TileMap::empty(16, 16);
+            let start = Coordinates { x: 8, y: 8 };
+            let excluded: Vec<Coordinates> = std::iter::once(start)
+                .chain(tile_map.safe_square_at(start))
+                .collect();
+            let report =
+                generate_no_guess(&mut tile_map, 40, start, &excluded, seed, &options()).unwrap();
+            assert!(report.solved, "seed {}", seed);
+            assert_eq!(tile_map.bomb_count(), 40);
+            assert!(excluded.iter().all(|c| !tile_map.is_bomb_at(*c)));
+            assert!(Solver::default().can_clear(&tile_map, start), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn search_stops_after_the_allowed_attempts() {
+        let mut tile_map = TileMap::empty(16, 16);
+        let start = Coordinates { x: 0, y: 0 };
+        let options = NoGuessOptions {
+            max_attempts: 1,
+            ..options()
+        };
+        // Far too many bombs to ever get a board without guesses
+        let report = generate_no_guess(&mut tile_map, 200, start, &[start], 3, &options).unwrap();
+        assert_eq!(report, NoGuessReport { attempts: 1, solved: false });
+    }
+}
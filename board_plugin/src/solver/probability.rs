@@ -0,0 +1,297 @@
+use super::enumeration::{self, Component};
+use super::{BoardKnowledge, Solver, SolverState};
+use crate::components::Coordinates;
+
+/// Fixed seed keeping the sampled probabilities stable between calls
+const SAMPLING_SEED: u64 = 0x5EED;
+
+/// Bomb probability of the covered tiles, given the player knowledge
+#[derive(Debug, Clone)]
+pub struct BombProbabilities {
+    width: u16,
+    height: u16,
+    // `None` for uncovered and flagged tiles
+    probabilities: Vec<Option<f64>>,
+    // are some probabilities estimated from sampled arrangements?
+    estimated: bool,
+}
+
+impl BombProbabilities {
+    /// Probability for the tile at 'coords' to be a bomb, `None` if it isn't an unknown tile
+    pub fn get(&self, coords: Coordinates) -> Option<f64> {
+        if coords.x < 0
+            || coords.y < 0
+            || coords.x >= self.width as i32
+            || coords.y >= self.height as i32
+        {
+            return None;
+        }
+        self.probabilities[coords.y as usize * self.width as usize + coords.x as usize]
+    }
+
+    /// Every unknown tile with its bomb probability
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinates, f64)> + '_ {
+        let width = self.width as usize;
+        self.probabilities
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, p)| {
+                p.map(|p| {
+                    let coords = Coordinates {
+                        x: (i % width) as i32,
+                        y: (i / width) as i32,
+                    };
+                    (coords, p)
+                })
+            })
+    }
+
+    /// Are some of the probabilities estimates? They are when a frontier group was too large
+    /// to be enumerated
+    pub fn is_estimate(&self) -> bool {
+        self.estimated
+    }
+
+    /// Unknown tile with the lowest bomb probability
+    pub fn safest(&self) -> Option<(Coordinates, f64)> {
+        self.iter()
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+    }
+}
+
+impl Solver {
+    /// Computes the bomb probability of every unknown tile from the revealed numbers, the flags
+    /// and the total bomb count, weighting every bomb arrangement equally.
+    ///
+    /// The arrangements of the frontier groups too large to be enumerated are sampled, so their
+    /// probabilities are approximations.
+    pub fn bomb_probabilities(&self, knowledge: &BoardKnowledge) -> BombProbabilities {
+        let state = SolverState::new(knowledge);
+        let mut result = BombProbabilities {
+            width: knowledge.width(),
+            height: knowledge.height(),
+            probabilities: vec![None; knowledge.len()],
+            estimated: false,
+        };
+        let remaining = match state.remaining_bombs() {
+            Some(r) => r,
+            None => return result,
+        };
+        let constraints = state.constraints();
+        let mut estimated = false;
+        // Groups without any arrangement found are left with the tiles away from the frontier
+        let components: Vec<Component> = enumeration::components(
+            &constraints,
+            self.max_enumeration_cells,
+            self.max_enumeration_steps,
+        )
+        .into_iter()
+        .map(|mut component| {
+            if component.arrangements.is_none() {
+                estimated = true;
+                let samples = enumeration::sample(
+                    &component,
+                    &constraints,
+                    self.probability_samples,
+                    self.max_enumeration_steps,
+                    SAMPLING_SEED,
+                );
+                component.arrangements = Some(samples).filter(|s| !s.is_empty());
+            }
+            component
+        })
+        .filter(|c| c.arrangements.is_some())
+        .collect();
+        result.estimated = estimated;
+        let probabilities = &mut result.probabilities;
+        let mut frontier = vec![false; knowledge.len()];
+        for cell in components.iter().flat_map(|c| c.cells.iter()) {
+            frontier[*cell] = true;
+        }
+        let interior: Vec<usize> = (0..knowledge.len())
+            .filter(|i| state.known[*i].is_none() && !frontier[*i])
+            .collect();
+
+        let ln_choose = LnChoose::new(interior.len());
+        let distributions: Vec<Vec<f64>> = components.iter().map(distribution).collect();
+
+        // Tiles of the enumerated groups
+        for (i, component) in components.iter().enumerate() {
+            let others = convolve_all(&distributions, Some(i), remaining);
+            let arrangements = component.arrangements.as_ref().unwrap();
+            // Weight of the arrangements with 'k' bombs, relative to each other
+            let ln_weights: Vec<(usize, f64)> = arrangements
+                .keys()
+                .filter_map(|k| {
+                    let ln_weight = ln_sum((0..others.len()).filter_map(|s| {
+                        let interior_bombs = remaining.checked_sub(k + s)?;
+                        Some(others[s].ln() + ln_choose.get(interior.len(), interior_bombs)?)
+                    }))?;
+                    Some((*k, ln_weight))
+                })
+                .collect();
+            let max = ln_weights
+                .iter()
+                .map(|(_, w)| *w)
+                .fold(f64::NEG_INFINITY, f64::max);
+            let mut total = 0.;
+            let mut tallies = vec![0.; component.cells.len()];
+            for (k, ln_weight) in ln_weights {
+                let weight = (ln_weight - max).exp();
+                let a = &arrangements[&k];
+                total += a.count as f64 * weight;
+                for (tally, t) in tallies.iter_mut().zip(a.bomb_tallies.iter()) {
+                    *tally += *t as f64 * weight;
+                }
+            }
+            if total > 0. {
+                for (cell, tally) in component.cells.iter().zip(tallies) {
+                    probabilities[*cell] = Some(tally / total);
+                }
+            }
+        }
+
+        // Tiles away from the frontier all share the same probability
+        if !interior.is_empty() {
+            let all = convolve_all(&distributions, None, remaining);
+            let mut total = 0.;
+            let mut bombs = 0.;
+            let terms: Vec<(f64, usize)> = (0..all.len())
+                .filter_map(|s| {
+                    let interior_bombs = remaining.checked_sub(s)?;
+                    let ln_weight = all[s].ln() + ln_choose.get(interior.len(), interior_bombs)?;
+                    Some((ln_weight, interior_bombs))
+                })
+                .filter(|(w, _)| w.is_finite())
+                .collect();
+            let max = terms.iter().map(|(w, _)| *w).fold(f64::NEG_INFINITY, f64::max);
+            for (ln_weight, interior_bombs) in terms {
+                let weight = (ln_weight - max).exp();
+                total += weight;
+                bombs += weight * interior_bombs as f64;
+            }
+            if total > 0. {
+                let probability = bombs / total / interior.len() as f64;
+                for cell in interior {
+                    probabilities[cell] = Some(probability);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Number of arrangements of a component by bomb count
+fn distribution(component: &Component) -> Vec<f64> {
+    let arrangements = component.arrangements.as_ref().unwrap();
+    let max = arrangements.keys().last().copied().unwrap_or(0);
+    let mut distribution = vec![0.; max + 1];
+    for (k, a) in arrangements.iter() {
+        distribution[*k] = a.count as f64;
+    }
+    distribution
+}
+
+/// Number of arrangements of all the components (but 'skip') by total bomb count, up to 'max'.
+/// Values are rescaled as they grow, only their ratios are meaningful
+fn convolve_all(distributions: &[Vec<f64>], skip: Option<usize>, max: usize) -> Vec<f64> {
+    let mut result = vec![1.];
+    for (i, distribution) in distributions.iter().enumerate() {
+        if Some(i) == skip {
+            continue;
+        }
+        let len = (result.len() + distribution.len() - 1).min(max + 1);
+        let mut next = vec![0.; len];
+        for (s, a) in result.iter().enumerate() {
+            for (k, b) in distribution.iter().enumerate() {
+                if s + k < len {
+                    next[s + k] += a * b;
+                }
+            }
+        }
+        let scale = next.iter().copied().fold(0., f64::max);
+        if scale > 0. {
+            next.iter_mut().for_each(|v| *v /= scale);
+        }
+        result = next;
+    }
+    result
+}
+
+/// Logarithm of a sum of values given by their logarithms, `None` if there is no finite term
+fn ln_sum(ln_values: impl Iterator<Item = f64>) -> Option<f64> {
+    let ln_values: Vec<f64> = ln_values.filter(|v| v.is_finite()).collect();
+    let max = ln_values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if !max.is_finite() {
+        return None;
+    }
+    Some(max + ln_values.iter().map(|v| (v - max).exp()).sum::<f64>().ln())
+}
+
+/// Logarithms of binomial coefficients, which overflow quickly on large boards
+struct LnChoose {
+    ln_factorials: Vec<f64>,
+}
+
+impl LnChoose {
+    fn new(max: usize) -> Self {
+        let mut ln_factorials = Vec::with_capacity(max + 1);
+        ln_factorials.push(0.);
+        for n in 1..=max {
+            ln_factorials.push(ln_factorials[n - 1] + (n as f64).ln());
+        }
+        Self { ln_factorials }
+    }
+
+    /// ln(n choose k), `None` if k > n
+    fn get(&self, n: usize, k: usize) -> Option<f64> {
+        if k > n {
+            return None;
+        }
+        Some(self.ln_factorials[n] - self.ln_factorials[k] - self.ln_factorials[n - k])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::tile_map::TileMap;
+    use crate::solver::CellKnowledge;
+    use crate::testing::{opened_knowledge, seeded_tile_map};
+
+    fn assert_sum_to_the_bomb_count(solver: &Solver, estimate: bool) {
+        for seed in 0..10 {
+            let knowledge = opened_knowledge(&seeded_tile_map(seed));
+            let probabilities = solver.bomb_probabilities(&knowledge);
+            assert_eq!(probabilities.is_estimate(), estimate);
+            assert!(probabilities.iter().all(|(_, p)| (0. ..=1.).contains(&p)));
+            let sum: f64 = probabilities.iter().map(|(_, p)| p).sum();
+            assert!((sum - 40.).abs() < 1e-6, "seed {}: expected 40 bombs, got {}", seed, sum);
+        }
+    }
+
+    #[test]
+    fn enumerated_probabilities_sum_to_the_bomb_count() {
+        assert_sum_to_the_bomb_count(&Solver::default(), false);
+    }
+
+    #[test]
+    fn sampled_probabilities_sum_to_the_bomb_count() {
+        let solver = Solver {
+            max_enumeration_cells: 0,
+            ..Default::default()
+        };
+        assert_sum_to_the_bomb_count(&solver, true);
+    }
+
+    #[test]
+    fn flags_are_left_out() {
+        let mut knowledge = BoardKnowledge::covered(&TileMap::empty(3, 2), 1);
+        knowledge.set(Coordinates { x: 0, y: 0 }, CellKnowledge::Flagged);
+        knowledge.set(Coordinates { x: 1, y: 0 }, CellKnowledge::Revealed(1));
+        let probabilities = Solver::default().bomb_probabilities(&knowledge);
+        assert_eq!(probabilities.get(Coordinates { x: 0, y: 0 }), None);
+        assert_eq!(probabilities.get(Coordinates { x: 1, y: 0 }), None);
+        assert_eq!(probabilities.iter().map(|(_, p)| p).sum::<f64>(), 0.);
+    }
+}
@@ -0,0 +1,259 @@
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::ElementState;
+use bevy::log;
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+use bevy::utils::{AHashExt, HashMap, HashSet};
+use crate::components::{Flag, FlagSprite};
+use crate::endless::{ChunkCoordinates, CHUNK_SIZE};
+use crate::plugin::{spawn_cover, spawn_tile_content};
+use crate::resources::endless_board::{ChunkView, EndlessBoard};
+use crate::resources::BoardAssets;
+use crate::systems::mark::spawn_flag;
+
+/// Chunks spawned around the visible ones, so that panning doesn't show the board edges
+const CHUNK_MARGIN: i32 = 1;
+
+/// Camera panning speed, in tiles per second
+const CAMERA_SPEED: f32 = 12.;
+
+/// Reveals tiles on left clicks and flags them on right clicks
+pub fn endless_input_handling(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    board: Option<ResMut<EndlessBoard>>,
+    board_assets: Res<BoardAssets>,
+    cameras: Query<&Transform, With<OrthographicProjection>>,
+    mut button_evr: EventReader<MouseButtonInput>,
+    flag_sprites: Query<(Entity, &Parent), With<FlagSprite>>,
+) {
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    let camera = match cameras.iter().next() {
+        Some(c) => c,
+        None => return,
+    };
+    let window = windows.get_primary().unwrap();
+    for event in button_evr.iter() {
+        if event.state != ElementState::Pressed {
+            continue;
+        }
+        let coordinates = match window.cursor_position() {
+            Some(pos) => board.mouse_position(window, camera, pos),
+            None => continue,
+        };
+        match event.button {
+            MouseButton::Left => {
+                log::info!("Trying to uncover tile on {}", coordinates);
+                let revealed = board.game.reveal(coordinates);
+                for tile in revealed.iter() {
+                    let chunk = ChunkCoordinates::of(tile.coordinates);
+                    let cover = board
+                        .chunks
+                        .get_mut(&chunk)
+                        .and_then(|view| view.covered_tiles.remove(&tile.coordinates));
+                    if let Some(entity) = cover {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                }
+                if board.game.is_over() {
+                    log::info!(
+                        "Boom ! {} tiles cleared before the explosion",
+                        board.game.score()
+                    );
+                }
+            }
+            MouseButton::Right => {
+                let flagged = match board.game.toggle_flag(coordinates) {
+                    None => continue,
+                    Some(v) => v,
+                };
+                let entity = match board.cover(coordinates) {
+                    None => continue,
+                    Some(e) => e,
+                };
+                if flagged {
+                    let size = board.sprite_size();
+                    commands
+                        .entity(entity)
+                        .insert(Flag)
+                        .with_children(|parent| spawn_flag(parent, 1, size, &board_assets));
+                } else {
+                    for (flag, parent) in flag_sprites.iter() {
+                        if parent.0 == entity {
+                            commands.entity(flag).despawn_recursive();
+                        }
+                    }
+                    commands.entity(entity).remove::<Flag>();
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Spawns the chunks entering the camera view and despawns the ones far from it. The game keeps
+/// the state of the despawned chunks
+pub fn chunk_streaming(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    board: Option<ResMut<EndlessBoard>>,
+    board_assets: Res<BoardAssets>,
+    cameras: Query<&Transform, With<OrthographicProjection>>,
+) {
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    let camera = match cameras.iter().next() {
+        Some(c) => c,
+        None => return,
+    };
+    let window = windows.get_primary().unwrap();
+    let half_size = Vec2::new(window.width(), window.height()) / 2.;
+    let center = camera.translation.xy();
+    let min = ChunkCoordinates::of(board.world_coordinates(center - half_size));
+    let max = ChunkCoordinates::of(board.world_coordinates(center + half_size));
+    let in_range = |chunk: &ChunkCoordinates, margin: i32| {
+        (min.x - margin..=max.x + margin).contains(&chunk.x)
+            && (min.y - margin..=max.y + margin).contains(&chunk.y)
+    };
+
+    // Chunks are kept a bit further than they are spawned, so that they don't flicker on edges
+    let far: Vec<ChunkCoordinates> = board
+        .chunks
+        .keys()
+        .filter(|chunk| !in_range(chunk, CHUNK_MARGIN + 1))
+        .copied()
+        .collect();
+    for chunk in far {
+        if let Some(view) = board.chunks.remove(&chunk) {
+            commands.entity(view.entity).despawn_recursive();
+        }
+    }
+
+    let visible: HashSet<ChunkCoordinates> = (min.y - CHUNK_MARGIN..=max.y + CHUNK_MARGIN)
+        .flat_map(|y| (min.x - CHUNK_MARGIN..=max.x + CHUNK_MARGIN).map(move |x| ChunkCoordinates { x, y }))
+        .collect();
+    for chunk in visible {
+        if board.chunks.contains_key(&chunk) {
+            continue;
+        }
+        board.game.load_chunk(chunk);
+        let view = spawn_chunk(&mut commands, &board, chunk, &board_assets);
+        commands.entity(board.entity).push_children(&[view.entity]);
+        board.chunks.insert(chunk, view);
+    }
+}
+
+/// Pans the camera with the arrow or WASD keys
+pub fn camera_movement(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    board: Option<Res<EndlessBoard>>,
+    mut cameras: Query<&mut Transform, With<OrthographicProjection>>,
+) {
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    let mut direction = Vec2::ZERO;
+    if keys.any_pressed([KeyCode::Left, KeyCode::A]) {
+        direction.x -= 1.;
+    }
+    if keys.any_pressed([KeyCode::Right, KeyCode::D]) {
+        direction.x += 1.;
+    }
+    if keys.any_pressed([KeyCode::Down, KeyCode::S]) {
+        direction.y -= 1.;
+    }
+    if keys.any_pressed([KeyCode::Up, KeyCode::W]) {
+        direction.y += 1.;
+    }
+    if direction == Vec2::ZERO {
+        return;
+    }
+    let offset = direction.normalize() * CAMERA_SPEED * board.tile_size * time.delta_seconds();
+    for mut transform in cameras.iter_mut() {
+        transform.translation += offset.extend(0.);
+    }
+}
+
+/// Spawns the tiles of a loaded chunk, with covers on the tiles not revealed yet
+fn spawn_chunk(
+    commands: &mut Commands,
+    board: &EndlessBoard,
+    chunk: ChunkCoordinates,
+    board_assets: &BoardAssets,
+) -> ChunkView {
+    let size = board.sprite_size();
+    let mut covered_tiles = HashMap::with_capacity((CHUNK_SIZE * CHUNK_SIZE) as usize);
+    let origin = chunk.origin();
+    let entity = commands
+        .spawn()
+        .insert(Name::new(format!("Chunk ({}, {})", chunk.x, chunk.y)))
+        .insert(chunk)
+        .insert(Transform::from_xyz(
+            origin.x as f32 * board.tile_size,
+            origin.y as f32 * board.tile_size,
+            0.,
+        ))
+        .insert(GlobalTransform::default())
+        .with_children(|parent| {
+            for coordinates in chunk.tiles() {
+                let tile = match board.game.tile_at(coordinates) {
+                    Some(t) => t,
+                    None => continue,
+                };
+                let local = coordinates - origin;
+                let mut cmd = parent.spawn();
+                cmd.insert_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: board_assets.tile_material.color,
+                        custom_size: Some(Vec2::splat(size)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(
+                        (local.x as f32 + 0.5) * board.tile_size,
+                        (local.y as f32 + 0.5) * board.tile_size,
+                        1.,
+                    ),
+                    texture: board_assets.tile_material.texture.clone(),
+                    ..Default::default()
+                })
+                .insert(Name::new(format!("Tile ({}, {})", coordinates.x, coordinates.y)))
+                .insert(coordinates);
+                if !board.game.is_revealed(coordinates) {
+                    let flagged = board.game.is_flagged(coordinates);
+                    cmd.with_children(|parent| {
+                        let mut cover = spawn_cover(parent, size, board_assets);
+                        if flagged {
+                            cover
+                                .insert(Flag)
+                                .with_children(|parent| spawn_flag(parent, 1, size, board_assets));
+                        }
+                        covered_tiles.insert(coordinates, cover.id());
+                    });
+                }
+                spawn_tile_content(&mut cmd, &tile, size, board_assets);
+            }
+        })
+        .id();
+    ChunkView {
+        entity,
+        covered_tiles,
+    }
+}
+
+/// Moves the cameras over 'position'
+pub(crate) fn move_cameras(
+    cameras: &mut Query<&mut Transform, With<OrthographicProjection>>,
+    position: Vec2,
+) {
+    for mut transform in cameras.iter_mut() {
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
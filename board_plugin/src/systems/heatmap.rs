@@ -0,0 +1,121 @@
+use bevy::log;
+use bevy::prelude::*;
+use crate::components::{HeatmapLabel, HeatmapOverlay};
+use crate::plugin::percentage_text_bundle;
+use crate::resources::board::Board;
+use crate::events::HeatmapToggleEvent;
+use crate::resources::{BoardAssets, Heatmap};
+use crate::solver::{BoardKnowledge, Solver};
+
+/// Toggles the heatmap and recomputes it whenever the board changes
+pub fn heatmap_update(
+    mut commands: Commands,
+    board: Option<Res<Board>>,
+    mut heatmap: ResMut<Heatmap>,
+    mut heatmap_toggle_evr: EventReader<HeatmapToggleEvent>,
+    overlays: Query<Entity, With<HeatmapOverlay>>,
+) {
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    let toggled = heatmap_toggle_evr.iter().count() % 2 == 1;
+    // The solver only knows the classic rules
+    if toggled && !board.game.tile_map().has_standard_rules() {
+        log::info!("The heatmap is only available with the classic rules");
+        return;
+    }
+    if toggled {
+        heatmap.enabled = !heatmap.enabled;
+        log::info!("Heatmap {}", if heatmap.enabled { "enabled" } else { "disabled" });
+    }
+    if !(toggled || heatmap.enabled && board.is_changed()) {
+        return;
+    }
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !heatmap.enabled {
+        heatmap.probabilities = None;
+        return;
+    }
+
+    let probabilities = Solver::default().bomb_probabilities(&BoardKnowledge::from_board(&board));
+    let size = board.sprite_size();
+    for (coords, entity) in board.covered_tiles.iter() {
+        let probability = match probabilities.get(*coords) {
+            Some(p) => p as f32,
+            None => continue,
+        };
+        commands.entity(*entity).with_children(|parent| {
+            parent
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        // From green for safe tiles to red for bombs
+                        color: Color::rgba(probability, 1. - probability, 0., 0.6),
+                        custom_size: Some(Vec2::splat(size)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(0., 0., 0.5),
+                    ..Default::default()
+                })
+                .insert(Name::new("Heatmap Overlay"))
+                .insert(HeatmapOverlay);
+        });
+    }
+    heatmap.probabilities = Some(probabilities);
+}
+
+/// Shows the bomb probability of the hovered covered tile while the heatmap is enabled
+pub fn heatmap_hover(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    board: Option<Res<Board>>,
+    board_assets: Res<BoardAssets>,
+    heatmap: Res<Heatmap>,
+    mut labels: Query<(Entity, &mut Text, &mut Transform), With<HeatmapLabel>>,
+) {
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    let window = windows.get_primary().unwrap();
+    let hovered = window
+        .cursor_position()
+        .and_then(|pos| board.mouse_position(window, pos))
+        .filter(|coords| board.covered_tiles.contains_key(coords))
+        .and_then(|coords| {
+            let probabilities = heatmap.probabilities.as_ref()?;
+            Some((coords, probabilities.get(coords)?, probabilities.is_estimate()))
+        });
+    let (coords, probability, estimate) = match hovered {
+        Some(v) => v,
+        None => {
+            for (entity, _, _) in labels.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            return;
+        }
+    };
+
+    let bundle = percentage_text_bundle(probability, estimate, &board_assets, board.sprite_size());
+    // The label is a board child, placed above the hovered cover
+    let translation = board.tile_center(coords).extend(10.);
+    match labels.iter_mut().next() {
+        Some((_, mut text, mut transform)) => {
+            *text = bundle.text;
+            transform.translation = translation;
+        }
+        None => {
+            commands.entity(board.entity).with_children(|parent| {
+                parent
+                    .spawn_bundle(Text2dBundle {
+                        transform: Transform::from_translation(translation),
+                        ..bundle
+                    })
+                    .insert(Name::new("Heatmap Label"))
+                    .insert(HeatmapLabel);
+            });
+        }
+    }
+}
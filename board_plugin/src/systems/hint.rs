@@ -0,0 +1,167 @@
+use bevy::log;
+use bevy::prelude::*;
+use crate::components::Hint;
+use crate::resources::board::Board;
+use crate::events::{HintEvent, HintRequestEvent};
+use crate::resources::BoardAssets;
+use crate::solver::{BoardKnowledge, Solver};
+
+/// Answers the hint requests by highlighting a covered tile, provably safe if possible
+pub fn hint_request_handler(
+    mut commands: Commands,
+    board: Option<ResMut<Board>>,
+    board_assets: Res<BoardAssets>,
+    mut hint_request_evr: EventReader<HintRequestEvent>,
+    mut hint_ewr: EventWriter<HintEvent>,
+    hinted: Query<Entity, With<Hint>>,
+    mut covers: Query<(&mut Sprite, &mut Handle<Image>)>,
+) {
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    // Several requests on the same frame get the same answer
+    if hint_request_evr.iter().count() == 0 {
+        return;
+    }
+    // The solver only knows the classic rules
+    if !board.game.tile_map().has_standard_rules() {
+        log::info!("Hints are only available with the classic rules");
+        return;
+    }
+    // Only the last hint stays highlighted
+    for entity in hinted.iter() {
+        if let Ok((mut sprite, mut texture)) = covers.get_mut(entity) {
+            sprite.color = board_assets.covered_tile_material.color;
+            *texture = board_assets.covered_tile_material.texture.clone();
+        }
+        commands.entity(entity).remove::<Hint>();
+    }
+
+    let hint = match find_hint(&board) {
+        None => {
+            log::info!("No tile left to hint");
+            return;
+        }
+        Some(h) => h,
+    };
+    board.hints_used += 1;
+    log::info!("Hint: {}", hint.explanation);
+    if let Some(entity) = board.covered_tiles.get(&hint.coordinates) {
+        if let Ok((mut sprite, mut texture)) = covers.get_mut(*entity) {
+            sprite.color = board_assets.hint_material.color;
+            *texture = board_assets.hint_material.texture.clone();
+        }
+        commands.entity(*entity).insert(Hint);
+    }
+    hint_ewr.send(hint);
+}
+
+/// Picks the hinted tile using the solver on what the player knows
+fn find_hint(board: &Board) -> Option<HintEvent> {
+    if board.game.is_over() {
+        return None;
+    }
+    // Before a safe first click no bomb is placed yet, any unflagged tile of its safe zone
+    // will do, the center first
+    if board.game.pending_bombs().is_some() {
+        let tile_map = board.game.tile_map();
+        let safe_start = std::iter::once(tile_map.center())
+            .chain(tile_map.coordinates())
+            .find(|&c| {
+                !board.game.is_flagged(c) && board.game.first_click_safe_zone(c).contains(&c)
+            });
+        if let Some(coordinates) = safe_start {
+            return Some(HintEvent {
+                coordinates,
+                safe: true,
+                explanation: format!(
+                    "{} is safe because the first click never hits a bomb",
+                    coordinates
+                ),
+            });
+        }
+    }
+    let knowledge = BoardKnowledge::from_board(board);
+    let solver = Solver::default();
+    if let Some(deduction) = solver.deduce(&knowledge).into_iter().find(|d| !d.is_bomb) {
+        return Some(HintEvent {
+            coordinates: deduction.coordinates,
+            safe: true,
+            explanation: deduction.explanation(&knowledge),
+        });
+    }
+    let probabilities = solver.bomb_probabilities(&knowledge);
+    let (coordinates, probability) = probabilities.safest()?;
+    let about = if probabilities.is_estimate() { "about " } else { "" };
+    Some(HintEvent {
+        coordinates,
+        safe: false,
+        explanation: format!(
+            "No tile is provably safe, {} has the lowest risk with {}{:.0}% bomb probability",
+            coordinates,
+            about,
+            probability * 100.
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounds::Bounds2;
+    use crate::game::{Game, GameOptions};
+    use crate::resources::{BoardOptions, BoardPreset, SafeFirstClick};
+    use bevy::utils::HashMap;
+
+    fn board(game: Game) -> Board {
+        Board {
+            game,
+            bounds: Bounds2 {
+                position: Vec2::ZERO,
+                size: Vec2::ZERO,
+            },
+            tile_size: 0.,
+            tile_padding: 0.,
+            covered_tiles: HashMap::default(),
+            hints_used: 0,
+            preset: BoardPreset::Beginner,
+            options: BoardOptions::default(),
+            entity: Entity::from_raw(0),
+        }
+    }
+
+    fn game(safe_first_click: SafeFirstClick) -> Game {
+        Game::new(&GameOptions {
+            map_size: (9, 9),
+            bomb_count: 10,
+            safe_first_click,
+            seed: Some(0),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn first_hint_avoids_the_flagged_center() {
+        let mut game = game(SafeFirstClick::Tile);
+        let center = game.tile_map().center();
+        game.cycle_flag(center);
+        let hint = find_hint(&board(game)).unwrap();
+        assert!(hint.safe);
+        assert_ne!(hint.coordinates, center);
+    }
+
+    #[test]
+    fn finished_games_get_no_hint() {
+        let mut game = game(SafeFirstClick::Disabled);
+        let bomb = game
+            .tile_map()
+            .coordinates()
+            .find(|c| game.tile_map().tile_at(*c).map_or(false, |t| t.is_bomb()))
+            .unwrap();
+        game.reveal(bomb).unwrap();
+        assert!(game.is_over());
+        assert!(find_hint(&board(game)).is_none());
+    }
+}
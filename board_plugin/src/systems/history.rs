@@ -0,0 +1,223 @@
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use crate::components::{Coordinates, Flag, FlagSprite};
+use crate::events::{HistoryEvent, TilesRevealedEvent};
+use crate::game::{Action, ActionKind};
+use crate::plugin::spawn_cover;
+use crate::resources::board::Board;
+use crate::resources::BoardAssets;
+use crate::systems::mark::spawn_flag;
+use crate::systems::uncover::uncover_revealed;
+
+/// Undoes or redoes the player actions, restoring the tile covers and flags they changed
+pub fn history_event_handler(
+    mut commands: Commands,
+    board: Option<ResMut<Board>>,
+    board_assets: Res<BoardAssets>,
+    tiles: Query<(Entity, &Coordinates)>,
+    flag_sprites: Query<(Entity, &Parent), With<FlagSprite>>,
+    mut history_evr: EventReader<HistoryEvent>,
+    mut tiles_revealed_ewr: EventWriter<TilesRevealedEvent>,
+) {
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    for event in history_evr.iter() {
+        match event {
+            HistoryEvent::Undo => {
+                let action = match board.game.undo() {
+                    Some(a) => a,
+                    None => {
+                        log_refused_undo(&board);
+                        continue;
+                    }
+                };
+                log::info!("Undid {:?} on {}", action.kind, action.coordinates);
+                if action.is_fatal() {
+                    log::warn!(
+                        "Explosion undone, {} in this game",
+                        board.game.undone_explosions()
+                    );
+                }
+                if action.kind == ActionKind::Flag {
+                    let flags = action.flags.0;
+                    set_flags(&mut commands, &board, &board_assets, &action, flags, &flag_sprites);
+                }
+                cover_tiles(&mut commands, &mut board, &board_assets, &action, &tiles);
+            }
+            HistoryEvent::Redo => {
+                let action = match board.game.redo() {
+                    Some(a) => a,
+                    None => {
+                        log::info!("Nothing to redo");
+                        continue;
+                    }
+                };
+                log::info!("Redid {:?} on {}", action.kind, action.coordinates);
+                if action.kind == ActionKind::Flag {
+                    let flags = action.flags.1;
+                    set_flags(&mut commands, &board, &board_assets, &action, flags, &flag_sprites);
+                }
+                uncover_revealed(&mut commands, &board, action.revealed, &mut tiles_revealed_ewr);
+            }
+        }
+    }
+}
+
+/// Explains why the game refused an undo
+fn log_refused_undo(board: &Board) {
+    match board.game.history().last() {
+        None => log::info!("Nothing to undo"),
+        Some(action) if action.is_fatal() => {
+            log::info!("Explosions can only be undone in practice mode")
+        }
+        Some(_) => (),
+    }
+}
+
+/// Spawns back the covers of the tiles an undone action revealed
+fn cover_tiles(
+    commands: &mut Commands,
+    board: &mut Board,
+    board_assets: &BoardAssets,
+    action: &Action,
+    tiles: &Query<(Entity, &Coordinates)>,
+) {
+    if action.revealed.is_empty() {
+        return;
+    }
+    let revealed: HashSet<Coordinates> =
+        action.revealed.iter().map(|tile| tile.coordinates).collect();
+    let size = board.sprite_size();
+    for (entity, coords) in tiles.iter() {
+        if !revealed.contains(coords) {
+            continue;
+        }
+        commands.entity(entity).with_children(|parent| {
+            let cover = spawn_cover(parent, size, board_assets).id();
+            board.covered_tiles.insert(*coords, cover);
+        });
+    }
+}
+
+/// Replaces the flag sprites of the cover of the flag action tile with 'flags' flags
+fn set_flags(
+    commands: &mut Commands,
+    board: &Board,
+    board_assets: &BoardAssets,
+    action: &Action,
+    flags: u8,
+    flag_sprites: &Query<(Entity, &Parent), With<FlagSprite>>,
+) {
+    let entity = match board.covered_tiles.get(&action.coordinates) {
+        None => return,
+        Some(e) => *e,
+    };
+    for (flag, parent) in flag_sprites.iter() {
+        if parent.0 == entity {
+            commands.entity(flag).despawn_recursive();
+        }
+    }
+    if flags > 0 {
+        let size = board.sprite_size();
+        commands
+            .entity(entity)
+            .insert(Flag)
+            .with_children(|parent| spawn_flag(parent, flags, size, board_assets));
+    } else {
+        commands.entity(entity).remove::<Flag>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{BoardCompletedEvent, BoardErrorEvent, BombExplosionEvent, TileTriggerEvent};
+    use crate::game::{Game, GameOptions};
+    use crate::plugin::spawn_game_board;
+    use crate::resources::tile_map::TileMap;
+    use crate::resources::{BoardOptions, SpriteMaterial};
+    use crate::systems::uncover::{trigger_event_handler, uncover_tiles};
+    use crate::systems::BoardSystem;
+    use bevy::app::Events;
+    use bevy::ecs::system::CommandQueue;
+
+    fn board_assets() -> BoardAssets {
+        BoardAssets {
+            label: "Test".to_string(),
+            board_material: SpriteMaterial::default(),
+            tile_material: SpriteMaterial::default(),
+            covered_tile_material: SpriteMaterial::default(),
+            bomb_counter_font: Handle::default(),
+            bomb_counter_colors: BoardAssets::default_colors(),
+            flag_material: SpriteMaterial::default(),
+            bomb_material: SpriteMaterial::default(),
+            negative_bomb_material: SpriteMaterial::default(),
+            hint_material: SpriteMaterial::default(),
+        }
+    }
+
+    /// World holding a covered board with the resources of the uncover and history systems
+    fn world() -> World {
+        let mut world = World::new();
+        let board = TileMap::from_text("#####\n#####\nx###x\n").unwrap();
+        let game = Game::from_board_text(board, &GameOptions::default()).unwrap();
+        let board_assets = board_assets();
+        let mut queue = CommandQueue::default();
+        let board = spawn_game_board(
+            &mut Commands::new(&mut queue, &world),
+            game,
+            &BoardOptions::default(),
+            &board_assets,
+            WindowDescriptor::default(),
+        );
+        queue.apply(&mut world);
+        world.insert_resource(board);
+        world.insert_resource(board_assets);
+        world.insert_resource(Events::<TileTriggerEvent>::default());
+        world.insert_resource(Events::<TilesRevealedEvent>::default());
+        world.insert_resource(Events::<BoardErrorEvent>::default());
+        world.insert_resource(Events::<HistoryEvent>::default());
+        world.insert_resource(Events::<BombExplosionEvent>::default());
+        world.insert_resource(Events::<BoardCompletedEvent>::default());
+        world
+    }
+
+    #[test]
+    fn undo_in_the_frame_of_a_reveal_keeps_the_new_covers() {
+        let mut world = world();
+        let mut stage = SystemStage::single_threaded()
+            .with_system(trigger_event_handler.before(BoardSystem::Uncover))
+            .with_system(uncover_tiles.label(BoardSystem::Uncover))
+            .with_system(history_event_handler.after(BoardSystem::Uncover));
+        let start = Coordinates { x: 2, y: 2 };
+        world
+            .get_resource_mut::<Events<TileTriggerEvent>>()
+            .unwrap()
+            .send(TileTriggerEvent(start));
+        world
+            .get_resource_mut::<Events<HistoryEvent>>()
+            .unwrap()
+            .send(HistoryEvent::Undo);
+        for _ in 0..3 {
+            stage.run(&mut world);
+        }
+
+        let board = world.get_resource::<Board>().unwrap();
+        assert!(!board.game.is_revealed(start));
+        assert_eq!(board.covered_tiles.len(), board.game.covered_count());
+        assert!(board
+            .covered_tiles
+            .values()
+            .all(|&cover| world.get_entity(cover).is_some()));
+        let cover_count = board.covered_tiles.len();
+        let spawned_covers = world
+            .query::<&Name>()
+            .iter(&world)
+            .filter(|name| name.as_str() == "Tile Cover")
+            .count();
+        assert_eq!(spawned_covers, cover_count);
+    }
+}
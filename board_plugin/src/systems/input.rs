@@ -1,18 +1,31 @@
 use bevy::input::ElementState;
 use bevy::input::mouse::MouseButtonInput;
 use bevy::prelude::*;
-use crate::events::TileTriggerEvent;
-use crate::log;
+use crate::events::{
+    HeatmapToggleEvent, HintRequestEvent, HistoryEvent, LoadGameEvent, SaveGameEvent,
+    TileChordEvent, TileMarkEvent, TileTriggerEvent,
+};
+use bevy::log;
 
 use crate::resources::board::Board;
 
 pub fn input_handling (
     windows: Res<Windows>,
-    board: Res<Board>,
+    board: Option<Res<Board>>,
+    buttons: Res<Input<MouseButton>>,
     mut button_evr: EventReader<MouseButtonInput>,
     mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
+    mut tile_mark_ewr: EventWriter<TileMarkEvent>,
+    mut tile_chord_ewr: EventWriter<TileChordEvent>,
 ) {
+    // Nothing to interact with if the board generation failed
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
     let window = windows.get_primary().unwrap();
+    // Both buttons of a left + right chord can be pressed on the same frame
+    let mut chorded = false;
 
     for event in button_evr.iter() {
         if let ElementState::Pressed = event.state {
@@ -22,6 +35,21 @@ pub fn input_handling (
                 log::trace!("Mouse button pressed: {:?} at {}", event.button, pos);
                 let tile_coordinates = board.mouse_position(window, pos);
                 if let Some(coordinates) = tile_coordinates {
+                    // Middle click, or pressing a button while the other one is held
+                    let chord = match event.button {
+                        MouseButton::Middle => true,
+                        MouseButton::Left => buttons.pressed(MouseButton::Right),
+                        MouseButton::Right => buttons.pressed(MouseButton::Left),
+                        _ => false,
+                    };
+                    if chord {
+                        if !chorded {
+                            log::info!("Trying to chord tile on {}", coordinates);
+                            tile_chord_ewr.send(TileChordEvent(coordinates));
+                            chorded = true;
+                        }
+                        continue;
+                    }
                     match event.button {
                         MouseButton::Left => {
                             log::info!("Trying to uncover tile on {}", coordinates);
@@ -29,7 +57,7 @@ pub fn input_handling (
                         }
                         MouseButton::Right => {
                             log::info!("Trying to mark tile on {}", coordinates);
-                            // TODO: generate an event
+                            tile_mark_ewr.send(TileMarkEvent(coordinates));
                         }
                         _ => (),
                     }
@@ -37,4 +65,49 @@ pub fn input_handling (
             }
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn keyboard_input_handling(
+    keys: Res<Input<KeyCode>>,
+    mut hint_request_ewr: EventWriter<HintRequestEvent>,
+    mut heatmap_toggle_ewr: EventWriter<HeatmapToggleEvent>,
+) {
+    if keys.just_pressed(KeyCode::H) {
+        log::info!("Hint requested");
+        hint_request_ewr.send(HintRequestEvent);
+    }
+    if keys.just_pressed(KeyCode::P) {
+        heatmap_toggle_ewr.send(HeatmapToggleEvent);
+    }
+}
+
+/// Ctrl+Z undoes the last action, Ctrl+Y or Ctrl+Shift+Z redoes it. Also listened to once the
+/// game is over, so that a fatal click can be undone
+pub fn history_input_handling(
+    keys: Res<Input<KeyCode>>,
+    mut history_ewr: EventWriter<HistoryEvent>,
+) {
+    if !keys.any_pressed([KeyCode::LControl, KeyCode::RControl]) {
+        return;
+    }
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    if keys.just_pressed(KeyCode::Z) && !shift {
+        history_ewr.send(HistoryEvent::Undo);
+    } else if keys.just_pressed(KeyCode::Y) || keys.just_pressed(KeyCode::Z) {
+        history_ewr.send(HistoryEvent::Redo);
+    }
+}
+
+/// F5 saves the game and F9 loads the saved one, also once the game is over
+pub fn save_input_handling(
+    keys: Res<Input<KeyCode>>,
+    mut save_game_ewr: EventWriter<SaveGameEvent>,
+    mut load_game_ewr: EventWriter<LoadGameEvent>,
+) {
+    if keys.just_pressed(KeyCode::F5) {
+        save_game_ewr.send(SaveGameEvent);
+    }
+    if keys.just_pressed(KeyCode::F9) {
+        load_game_ewr.send(LoadGameEvent);
+    }
+}
@@ -0,0 +1,78 @@
+use bevy::log;
+use bevy::prelude::*;
+use crate::components::{Flag, FlagSprite};
+use crate::plugin::stack_count_text_bundle;
+use crate::resources::board::Board;
+use crate::events::TileMarkEvent;
+use crate::resources::BoardAssets;
+
+pub fn mark_tiles(
+    mut commands: Commands,
+    board: Option<ResMut<Board>>,
+    board_assets: Res<BoardAssets>,
+    mut tile_mark_evr: EventReader<TileMarkEvent>,
+    children: Query<&Children, With<Flag>>,
+    flag_sprites: Query<Entity, With<FlagSprite>>,
+) {
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    for mark_event in tile_mark_evr.iter() {
+        let flags = match board.game.cycle_flag(mark_event.0) {
+            None => continue,
+            Some(v) => v,
+        };
+        let entity = match board.covered_tiles.get(&mark_event.0) {
+            None => continue,
+            Some(e) => *e,
+        };
+        // The previous flag sprites are replaced, other children of the cover are kept
+        if let Ok(cover_children) = children.get(entity) {
+            for child in cover_children.iter() {
+                if flag_sprites.get(*child).is_ok() {
+                    commands.entity(*child).despawn_recursive();
+                }
+            }
+        }
+        if flags > 0 {
+            log::debug!("Flagged tile {} with {} flag(s)", mark_event.0, flags);
+            let size = board.sprite_size();
+            commands
+                .entity(entity)
+                .insert(Flag)
+                .with_children(|parent| spawn_flag(parent, flags, size, &board_assets));
+        } else {
+            log::debug!("Unflagged tile {}", mark_event.0);
+            commands.entity(entity).remove::<Flag>();
+        }
+    }
+}
+
+/// Spawns the flag sprite of a tile cover, with the flag count if there are several
+pub(crate) fn spawn_flag(
+    parent: &mut ChildBuilder,
+    flags: u8,
+    size: f32,
+    board_assets: &BoardAssets,
+) {
+    parent
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(size)),
+                color: board_assets.flag_material.color,
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0., 0., 1.),
+            texture: board_assets.flag_material.texture.clone(),
+            ..Default::default()
+        })
+        .insert(Name::new("Flag"))
+        .insert(FlagSprite);
+    if flags > 1 {
+        parent
+            .spawn_bundle(stack_count_text_bundle(flags, board_assets, size))
+            .insert(Name::new("Flag Count"))
+            .insert(FlagSprite);
+    }
+}
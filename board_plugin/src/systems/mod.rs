@@ -1,2 +1,19 @@
+use bevy::ecs::schedule::SystemLabel;
+
 pub mod input;
-pub mod uncover;
\ No newline at end of file
+pub mod uncover;
+pub mod mark;
+pub mod hint;
+pub mod heatmap;
+pub mod endless;
+pub mod history;
+pub mod stats;
+pub mod options_file;
+pub mod save;
+
+/// Labels of the board systems others must be ordered with
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum BoardSystem {
+    // Despawns the uncovered tile covers
+    Uncover,
+}
\ No newline at end of file
@@ -0,0 +1,83 @@
+use bevy::log;
+use bevy::prelude::*;
+use crate::events::BoardOptionsLoadedEvent;
+use crate::plugin::{despawn_board, spawn_board};
+use crate::resources::board::Board;
+use crate::resources::{
+    BoardAssets, BoardOptions, BoardOptionsAsset, BoardOptionsError, BoardOptionsFile,
+};
+
+/// Merges the options of the options file into the board options when it is loaded or
+/// modified on disk. The board is only regenerated when the file is modified, the first load
+/// only changes the options of the next board
+pub fn options_file_handler(
+    mut commands: Commands,
+    options_file: Option<Res<BoardOptionsFile>>,
+    board_options: Option<Res<BoardOptions>>,
+    option_assets: Res<Assets<BoardOptionsAsset>>,
+    mut asset_evr: EventReader<AssetEvent<BoardOptionsAsset>>,
+    mut options_loaded_ewr: EventWriter<BoardOptionsLoadedEvent>,
+) {
+    let options_file = match options_file {
+        Some(f) => f,
+        None => return,
+    };
+    let mut options = board_options.map(|o| o.clone());
+    for event in asset_evr.iter() {
+        let (handle, modified) = match event {
+            AssetEvent::Created { handle } => (handle, false),
+            AssetEvent::Modified { handle } => (handle, true),
+            AssetEvent::Removed { .. } => continue,
+        };
+        if *handle != options_file.0 {
+            continue;
+        }
+        let asset = match option_assets.get(handle) {
+            Some(asset) => asset,
+            None => continue,
+        };
+        // The preset selected while playing is kept
+        let merged = match &options {
+            Some(current) => current.merge_file(&asset.0),
+            None => asset.0.clone(),
+        };
+        if modified {
+            log::info!("Board options file modified");
+            options_loaded_ewr.send(BoardOptionsLoadedEvent(merged.clone()));
+        } else {
+            log::info!("Board options file loaded");
+        }
+        options = Some(merged);
+    }
+    if let Some(options) = options {
+        commands.insert_resource(options);
+    }
+}
+
+/// Regenerates the board with the options of a modified options file. Once the game is over the
+/// new options are only used by the next board
+pub fn options_loaded_handler(
+    mut commands: Commands,
+    board: Option<Res<Board>>,
+    board_assets: Res<BoardAssets>,
+    window: Option<Res<WindowDescriptor>>,
+    mut options_loaded_evr: EventReader<BoardOptionsLoadedEvent>,
+) -> Result<(), BoardOptionsError> {
+    // Only the last version of the file matters
+    let options = match options_loaded_evr.iter().last() {
+        Some(event) => event.0.clone(),
+        None => return Ok(()),
+    };
+    if let Some(board) = board {
+        if board.game.is_over() {
+            return Ok(());
+        }
+        despawn_board(&mut commands, &board);
+    }
+    let window_options = match window {
+        None => WindowDescriptor::default(),
+        Some(o) => o.clone(),
+    };
+    log::info!("Regenerating the board with the modified options");
+    spawn_board(&mut commands, &options, &board_assets, window_options)
+}
@@ -0,0 +1,118 @@
+use bevy::app::AppExit;
+use bevy::log;
+use bevy::prelude::*;
+use crate::events::{LoadGameEvent, SaveGameEvent};
+use crate::game::Game;
+use crate::plugin::{despawn_board, spawn_game_board};
+use crate::resources::board::Board;
+use crate::resources::{BoardAssets, BoardSave, GameStats, SaveError, SaveFile, SAVE_VERSION};
+
+/// Writes the game to the save file, finished games are not saved
+pub fn save_event_handler(
+    board: Option<Res<Board>>,
+    stats: Option<Res<GameStats>>,
+    save_file: Res<SaveFile>,
+    mut save_game_evr: EventReader<SaveGameEvent>,
+) {
+    if save_game_evr.iter().count() == 0 {
+        return;
+    }
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    if board.game.is_over() {
+        log::info!("The game is over, there is nothing to save");
+        return;
+    }
+    match save_game(&board, stats.as_deref(), &save_file) {
+        Ok(()) => log::info!("Game saved to {}", save_file.0.display()),
+        Err(e) => log::error!("Failed to save the game: {}", e),
+    }
+}
+
+/// Replaces the board with the one of the save file
+pub fn load_event_handler(
+    mut commands: Commands,
+    board: Option<Res<Board>>,
+    board_assets: Res<BoardAssets>,
+    window: Option<Res<WindowDescriptor>>,
+    save_file: Res<SaveFile>,
+    mut load_game_evr: EventReader<LoadGameEvent>,
+) {
+    if load_game_evr.iter().count() == 0 {
+        return;
+    }
+    let save = match BoardSave::read(&save_file.0) {
+        Ok(s) => s,
+        Err(e) => {
+            log::error!("Failed to load the game: {}", e);
+            return;
+        }
+    };
+    let game = match Game::from_save(save.game) {
+        Some(g) => g,
+        None => {
+            log::error!("Failed to load the game: {}", SaveError::InvalidGame);
+            return;
+        }
+    };
+    if let Some(board) = board {
+        despawn_board(&mut commands, &board);
+    }
+    let window_options = match window {
+        None => WindowDescriptor::default(),
+        Some(o) => o.clone(),
+    };
+    let mut board = spawn_game_board(
+        &mut commands,
+        game,
+        &save.options,
+        &board_assets,
+        window_options,
+    );
+    board.hints_used = save.hints_used;
+    commands.insert_resource(board);
+    commands.insert_resource(GameStats {
+        elapsed: save.elapsed,
+        started: !save.elapsed.is_zero(),
+        ..Default::default()
+    });
+    commands.insert_resource(save.options);
+    log::info!("Game loaded from {}", save_file.0.display());
+}
+
+/// Saves the game in progress when the app exits
+pub fn save_on_exit(
+    board: Option<Res<Board>>,
+    stats: Option<Res<GameStats>>,
+    save_file: Res<SaveFile>,
+    mut app_exit_evr: EventReader<AppExit>,
+) {
+    if app_exit_evr.iter().count() == 0 {
+        return;
+    }
+    let board = match board {
+        Some(b) if !b.game.is_over() => b,
+        _ => return,
+    };
+    if let Err(e) = save_game(&board, stats.as_deref(), &save_file) {
+        log::error!("Failed to save the game: {}", e);
+    }
+}
+
+/// Writes the game of 'board' to the save file, with the options it was created with
+pub(crate) fn save_game(
+    board: &Board,
+    stats: Option<&GameStats>,
+    save_file: &SaveFile,
+) -> Result<(), SaveError> {
+    let save = BoardSave {
+        version: SAVE_VERSION,
+        options: board.options.clone(),
+        game: board.game.to_save(),
+        elapsed: stats.map(|s| s.elapsed).unwrap_or_default(),
+        hints_used: board.hints_used,
+    };
+    save.write(&save_file.0)
+}
@@ -0,0 +1,66 @@
+use bevy::log;
+use bevy::prelude::*;
+use crate::events::{
+    BoardCompletedEvent, BombExplosionEvent, TileChordEvent, TileMarkEvent, TileTriggerEvent,
+    TilesRevealedEvent,
+};
+use crate::resources::board::Board;
+use crate::resources::GameStats;
+
+/// Counts the player clicks, the clock starts on the first click revealing tiles. Clicks on
+/// flagged or revealed tiles count but don't start it
+pub fn click_counter(
+    stats: Option<ResMut<GameStats>>,
+    mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+    mut tile_mark_evr: EventReader<TileMarkEvent>,
+    mut tile_chord_evr: EventReader<TileChordEvent>,
+    mut tiles_revealed_evr: EventReader<TilesRevealedEvent>,
+) {
+    let mut stats = match stats {
+        Some(s) => s,
+        None => return,
+    };
+    if tiles_revealed_evr.iter().count() > 0 {
+        stats.started = true;
+    }
+    stats.left_clicks += tile_trigger_evr.iter().count() as u32;
+    stats.right_clicks += tile_mark_evr.iter().count() as u32;
+    stats.chord_clicks += tile_chord_evr.iter().count() as u32;
+}
+
+/// Runs the clock while the game goes on, follows the cleared 3BV and reports the stats when the
+/// game ends
+pub fn stats_update(
+    time: Res<Time>,
+    board: Option<Res<Board>>,
+    stats: Option<ResMut<GameStats>>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+    mut board_completed_evr: EventReader<BoardCompletedEvent>,
+) {
+    let (board, mut stats) = match (board, stats) {
+        (Some(b), Some(s)) => (b, s),
+        _ => return,
+    };
+    if stats.started && !board.game.is_over() {
+        stats.elapsed += time.delta();
+    }
+    // Before the first click places the bombs there is no 3BV to clear
+    if board.is_changed() && board.game.pending_bombs().is_none() {
+        stats.bbbv = board.game.bbbv();
+        stats.solved_bbbv = board.game.solved_bbbv();
+    }
+    let lost = bomb_explosion_evr.iter().count() > 0;
+    let won = board_completed_evr.iter().count() > 0;
+    if !(lost || won) {
+        return;
+    }
+    log::info!("{} game stats: {}", board.preset.name(), *stats);
+    // Undoing an explosion makes the game a practice one
+    let undone_explosions = board.game.undone_explosions();
+    if undone_explosions > 0 {
+        log::info!(
+            "Practice game with {} undone explosion(s), not ranked",
+            undone_explosions
+        );
+    }
+}
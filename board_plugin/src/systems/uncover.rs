@@ -1,27 +1,115 @@
 use bevy::log;
 use bevy::prelude::*;
-use crate::{Bomb, Board, BombNeighbor, Coordinates, Uncover};
-use crate::events::TileTriggerEvent;
+use crate::components::{Coordinates, Uncover};
+use crate::events::{
+    BoardCompletedEvent, BoardErrorEvent, BombExplosionEvent, TileChordEvent, TileTriggerEvent,
+    TilesRevealedEvent,
+};
+use crate::game::{GameOutcome, RevealedTile};
+use crate::plugin::{log_no_guess_report, spawn_tile_content};
+use crate::resources::board::Board;
+use crate::resources::BoardAssets;
 
 pub fn trigger_event_handler(
     mut commands: Commands,
-    board: Res<Board>,
+    board: Option<ResMut<Board>>,
+    board_assets: Res<BoardAssets>,
+    tiles: Query<(Entity, &Coordinates)>,
     mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+    mut tiles_revealed_ewr: EventWriter<TilesRevealedEvent>,
+    mut board_error_ewr: EventWriter<BoardErrorEvent>,
 ) {
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
     for trigger_event in tile_trigger_evr.iter() {
-        if let Some(entity) = board.tile_to_uncover(&trigger_event.0) {
+        let pending = board.game.pending_bombs().is_some();
+        let revealed = match board.game.reveal(trigger_event.0) {
+            Ok(revealed) => revealed,
+            Err(e) => {
+                log::error!("Failed to place the bombs: {}", e);
+                board_error_ewr.send(BoardErrorEvent(e));
+                continue;
+            }
+        };
+        // With a safe first click the bombs were placed around the first revealed tile
+        if pending && board.game.pending_bombs().is_none() {
+            if let Some(report) = board.game.no_guess_report() {
+                log_no_guess_report(report);
+            }
+            #[cfg(feature = "debug")]
+            // Tilemap debugging
+            log::info!("{}", board.game.tile_map().console_output());
+            for (tile_entity, coords) in tiles.iter() {
+                if let Some(tile) = board.game.tile_map().tile_at(*coords) {
+                    spawn_tile_content(
+                        &mut commands.entity(tile_entity),
+                        tile,
+                        board.sprite_size(),
+                        &board_assets,
+                    );
+                }
+            }
+        }
+        uncover_revealed(&mut commands, &board, revealed, &mut tiles_revealed_ewr);
+    }
+}
+
+/// Uncovers the neighbors of a revealed bomb neighbor when its count matches the adjacent flags.
+/// A misplaced flag means a bomb gets uncovered, going through the regular explosion path
+pub fn chord_event_handler(
+    mut commands: Commands,
+    board: Option<ResMut<Board>>,
+    mut tile_chord_evr: EventReader<TileChordEvent>,
+    mut tiles_revealed_ewr: EventWriter<TilesRevealedEvent>,
+) {
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    for chord_event in tile_chord_evr.iter() {
+        let revealed = board.game.chord(chord_event.0);
+        if revealed.is_empty() {
+            log::debug!("Chord on {} doesn't reveal anything", chord_event.0);
+            continue;
+        }
+        uncover_revealed(&mut commands, &board, revealed, &mut tiles_revealed_ewr);
+    }
+}
+
+/// Marks the covers of the tiles revealed by the game to be uncovered, all on the next frame
+pub(crate) fn uncover_revealed(
+    commands: &mut Commands,
+    board: &Board,
+    revealed: Vec<RevealedTile>,
+    tiles_revealed_ewr: &mut EventWriter<TilesRevealedEvent>,
+) {
+    if revealed.is_empty() {
+        return;
+    }
+    for tile in revealed.iter() {
+        if let Some(entity) = board.covered_tiles.get(&tile.coordinates) {
             commands.entity(*entity).insert(Uncover);
         }
     }
+    tiles_revealed_ewr.send(TilesRevealedEvent(revealed));
 }
 
-// First query written
+/// Despawns the covers of the tiles revealed by the game and reports its outcome
 pub fn uncover_tiles(
     mut commands: Commands, // included for entity manipulation
-    mut board: ResMut<Board>, //ResMut: uniquye mutable borrow of a resource
+    board: Option<ResMut<Board>>, //ResMut: uniquye mutable borrow of a resource
     children: Query<(Entity, &Parent), With<Uncover>>,
-    parents: Query<(&Coordinates, Option<&Bomb>, Option<&BombNeighbor>)>
+    parents: Query<&Coordinates>,
+    mut bomb_explosion_ewr: EventWriter<BombExplosionEvent>,
+    mut board_completed_ewr: EventWriter<BoardCompletedEvent>,
 ) {
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    let mut uncovered = false;
     // Iterate through tile covers to uncover
     for (entity, parent) in children.iter() {
         commands
@@ -30,7 +118,7 @@ pub fn uncover_tiles(
             // the board tile entity
             .despawn_recursive();
 
-        let (coords, bomb, bomb_counter) = match parents.get(parent.0) {
+        let coords = match parents.get(parent.0) {
             Ok(v) => v,
             Err(e) => {
                 log::error!("{:?}", e); //RFC?
@@ -38,23 +126,23 @@ pub fn uncover_tiles(
             }
         };
 
-        match board.try_uncover_tile(coords) {
-            None => log::debug!("Tried to uncover an already uncovered tile"),
-            Some(e) => log::debug!("Uncovered tile {} (entity: {:?})", coords, e),
+        // An undo may already have put a new cover on the tile, which stays
+        if board.covered_tiles.get(coords) == Some(&entity) {
+            board.covered_tiles.remove(coords);
+            log::debug!("Uncovered tile {} (entity: {:?})", coords, entity);
+            uncovered = true;
+        } else {
+            log::debug!("Tried to uncover an already uncovered tile");
         }
-        if bomb.is_some() {
+        if board.game.outcome() == (GameOutcome::Lost { exploded: *coords }) {
             log::info!("Boom !");
-            // TODO: Add explosion event
-        }
-        // If the tile is empty
-        else if bomb_counter.is_none() {
-            // .. Propagate the uncovering by adding the 'Uncover' component to adjacent tiles
-            // which will then be removed next frame
-            for entity in board.adjacent_covered_tiles(*coords) {
-                commands.entity(entity).insert(Uncover);
-            }
+            bomb_explosion_ewr.send(BombExplosionEvent(*coords));
         }
     }
 
-
-}
\ No newline at end of file
+    // The covers of the winning reveal are all uncovered on the same frame
+    if uncovered && board.game.outcome() == GameOutcome::Won {
+        log::info!("Board completed");
+        board_completed_ewr.send(BoardCompletedEvent);
+    }
+}
@@ -0,0 +1,40 @@
+//! Fixtures shared by the unit tests
+use crate::components::Coordinates;
+use crate::resources::tile::Tile;
+use crate::resources::tile_map::TileMap;
+use crate::solver::BoardKnowledge;
+
+/// 16x16 map with 40 bombs placed from 'seed'
+pub fn seeded_tile_map(seed: u64) -> TileMap {
+    let mut tile_map = TileMap::empty(16, 16);
+    tile_map.set_bombs(40, seed).unwrap();
+    tile_map
+}
+
+/// First tile without bomb neighbors
+pub fn first_empty_tile(tile_map: &TileMap) -> Coordinates {
+    tile_map
+        .coordinates()
+        .find(|c| tile_map.tile_at(*c) == Some(&Tile::Empty))
+        .expect("the map has an empty tile")
+}
+
+/// Map whose bombs are exactly at 'bombs'
+pub fn tile_map_with_bombs(width: u16, height: u16, bombs: &[Coordinates]) -> TileMap {
+    let mut tile_map = TileMap::empty(width, height);
+    let excluded: Vec<Coordinates> = tile_map
+        .coordinates()
+        .filter(|c| !bombs.contains(c))
+        .collect();
+    tile_map
+        .set_bombs_excluding(bombs.len() as u16, &excluded, 0)
+        .unwrap();
+    tile_map
+}
+
+/// Player knowledge once the opening around the first empty tile is revealed
+pub fn opened_knowledge(tile_map: &TileMap) -> BoardKnowledge {
+    let mut knowledge = BoardKnowledge::covered(tile_map, tile_map.bomb_count());
+    knowledge.reveal(tile_map, first_empty_tile(tile_map));
+    knowledge
+}
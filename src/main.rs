@@ -1,16 +1,20 @@
 use bevy::prelude::*;
 use core::default::Default as Def;
-use board_plugin::BoardPlugin;
-use board_plugin::resources::{BoardAssets, BoardOptions, SpriteMaterial};
+use board_plugin::{BoardPlugin, EndlessBoardPlugin};
+use board_plugin::resources::{
+    BoardAssets, BoardOptions, BoardOptionsFile, BoardPreset, SpriteMaterial,
+};
 use bevy::log;
 
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::WorldInspectorPlugin;
-use crate::CursorIcon::Default;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum AppState {
     InGame,
+    Endless,
+    Won,
+    Lost,
     Out,
 }
 
@@ -26,15 +30,23 @@ fn main() {
         // Bevy default plugins
         .add_plugins(DefaultPlugins);
     #[cfg(feature = "debug")]
-        // Debug hierarchy inspector
-        app.add_plugin(WorldInspectorPlugin::new())
-        // Board plugin options
-        // is this the right place to put this?
-        .add_state(AppState::Out)
+    // Debug hierarchy inspector
+    app.add_plugin(WorldInspectorPlugin::new());
+    // Board plugin options
+    // is this the right place to put this?
+    app.add_state(AppState::Out)
         .add_plugin(BoardPlugin {
             running_state: AppState::InGame,
+            won_state: Some(AppState::Won),
+            lost_state: Some(AppState::Lost),
+        })
+        .add_plugin(EndlessBoardPlugin {
+            running_state: AppState::Endless,
+            lost_state: Some(AppState::Lost),
         })
         .add_system(state_handler)
+        .add_system_set(SystemSet::on_enter(AppState::Won).with_system(on_won))
+        .add_system_set(SystemSet::on_enter(AppState::Lost).with_system(on_lost))
         // Startup system (cameras)
         .add_startup_system(camera_setup)
         .add_startup_system(setup_board)
@@ -43,16 +55,28 @@ fn main() {
 }
 
 
-fn state_handler(mut game_state: ResMut<State<AppState>>, keys: Res<Input<KeyCode>>) {
-    let mut set_clear_state = |state: &mut ResMut<State<AppState>>| {
+fn state_handler(
+    mut game_state: ResMut<State<AppState>>,
+    mut board_options: ResMut<BoardOptions>,
+    keys: Res<Input<KeyCode>>,
+) {
+    let set_clear_state = |state: &mut ResMut<State<AppState>>| {
         log::debug!("clearing game");
-        if state.current() == &AppState::InGame {
-            log::info!("clearing game");
-            state.set(AppState::Out).unwrap();
+        match state.current() {
+            AppState::InGame | AppState::Endless => {
+                log::info!("clearing game");
+                state.set(AppState::Out).unwrap();
+            }
+            // The game outcome states are pushed on top of the running state
+            AppState::Won | AppState::Lost => {
+                log::info!("clearing game");
+                state.replace(AppState::Out).unwrap();
+            }
+            AppState::Out => (),
         }
     };
 
-    let mut set_gen_state = |state: &mut ResMut<State<AppState>>| {
+    let set_gen_state = |state: &mut ResMut<State<AppState>>| {
         log::debug!("loading detected");
         if state.current() == &AppState::Out {
             log::info!("loading game");
@@ -60,16 +84,60 @@ fn state_handler(mut game_state: ResMut<State<AppState>>, keys: Res<Input<KeyCod
         }
     };
 
+    // Endless mode, until the next generation
+    if keys.just_pressed(KeyCode::E) {
+        let result = match game_state.current() {
+            AppState::Endless => return,
+            AppState::Won | AppState::Lost => game_state.replace(AppState::Endless),
+            AppState::InGame | AppState::Out => game_state.set(AppState::Endless),
+        };
+        if let Err(e) = result {
+            log::error!("Failed to start the endless mode: {:?}", e);
+        }
+        log::info!("loading endless game");
+        return;
+    }
+
     //Generate
     if keys.just_pressed(KeyCode::G) {
         set_clear_state(&mut game_state);
     }
+    // Difficulty selection, a new board is generated with the selected preset
+    let preset = if keys.just_pressed(KeyCode::Key1) {
+        Some(BoardPreset::Beginner)
+    } else if keys.just_pressed(KeyCode::Key2) {
+        Some(BoardPreset::Intermediate)
+    } else if keys.just_pressed(KeyCode::Key3) {
+        Some(BoardPreset::Expert)
+    } else if keys.just_pressed(KeyCode::Key4) {
+        Some(classic_preset())
+    } else {
+        None
+    };
+    if let Some(preset) = preset {
+        log::info!("Selected the {} preset", preset.name());
+        board_options.preset = Some(preset);
+        set_clear_state(&mut game_state);
+    }
     // game_state needs to leave the scope to exit . . . kind of jank xD
     set_gen_state(&mut game_state);
 
     // TODO: implement pause
 }
 
+fn on_won() {
+    log::info!("You won ! Press G to generate a new board, or 1 to 4 to change the difficulty");
+}
+
+fn on_lost() {
+    log::info!("You lost ! Press G to generate a new board, or E for an endless one");
+}
+
+/// Custom preset the game starts with
+fn classic_preset() -> BoardPreset {
+    BoardPreset::custom("Classic", (20, 20), 40)
+}
+
 fn camera_setup(mut commands: Commands) {
     // 2D orthographic camera
     commands.spawn_bundle(OrthographicCameraBundle::new_2d());
@@ -80,10 +148,16 @@ fn setup_board(
     mut state: ResMut<State<AppState>>,
     asset_server: Res<AssetServer>,
 ) {
-    // Board plugin options
+    // Reloads the board options file when it is edited
+    if let Err(e) = asset_server.watch_for_changes() {
+        log::warn!("Asset hot reload unavailable: {:?}", e);
+    }
+    // Board plugin options, the options file is merged into them once it is loaded
+    commands.insert_resource(BoardOptionsFile(
+        asset_server.load("options/default.board.ron"),
+    ));
     commands.insert_resource(BoardOptions {
-        map_size: (20, 20),
-        bomb_count: 40,
+        preset: Some(classic_preset()),
         tile_padding: 1.,
         safe_start: true,
         ..Def::default()
@@ -112,7 +186,15 @@ fn setup_board(
         bomb_material: SpriteMaterial {
             texture: asset_server.load("sprites/bomb_emoji.png"),
             color: Color::WHITE,
-        }
+        },
+        negative_bomb_material: SpriteMaterial {
+            texture: asset_server.load("sprites/bomb_emoji.png"),
+            color: Color::CYAN,
+        },
+        hint_material: SpriteMaterial {
+            color: Color::AQUAMARINE,
+            ..Def::default()
+        },
     });
     // Plugin Activation
     state.set(AppState::InGame).unwrap();